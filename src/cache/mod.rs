@@ -0,0 +1,826 @@
+#![allow(unused)]
+//in-memory cache of discord entities, kept up to date by feeding it every received GatewayData
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::RwLock,
+};
+
+use derive_builder::Builder;
+use smartstring::alias::String;
+
+pub use self::snapshot::{CacheSnapshot, GuildSnapshot};
+
+use crate::{
+    dapi::routes::{
+        common_types::{ChannelId, EmojiId, GuildId, RoleId, UserId},
+        v10::{
+            permissions::PermissionCalculator,
+            types::{Channel, Emoji, Guild, GuildMember, Permissions, Role, User, VoiceState},
+        },
+    },
+    gateway::fake_types::{
+        GatewayData, GatewayGuild, GatewayGuildCreatePayload, GatewayGuildMemberUpdatePayload,
+        GatewayThreadListSyncPayload, GatewayThreadMembersUpdatePayload,
+    },
+};
+
+mod snapshot;
+
+bitflags::bitflags! {
+    pub struct CacheResources: u32 {
+        const GUILDS =       1 << 0;
+        const CHANNELS =     1 << 1;
+        const THREADS =      1 << 2;
+        const ROLES =        1 << 3;
+        const MEMBERS =      1 << 4;
+        const USERS =        1 << 5;
+        const EMOJIS =       1 << 6;
+        const VOICE_STATES = 1 << 7;
+    }
+}
+
+impl Default for CacheResources {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Which resources get cached and how many of each at most.
+/// Once a limit is reached new entries are dropped, while already cached ones keep getting updated.
+#[derive(Clone, Debug, Default, Builder)]
+#[builder(default, setter(into, strip_option))]
+pub struct CacheConfig {
+    pub resources: CacheResources,
+    pub max_guilds: Option<usize>,
+    pub max_channels: Option<usize>,
+    pub max_threads: Option<usize>,
+    pub max_roles: Option<usize>,
+    pub max_members: Option<usize>,
+    pub max_users: Option<usize>,
+    pub max_emojis: Option<usize>,
+    pub max_voice_states: Option<usize>,
+}
+
+/// A guild together with the ids of everything belonging to it.
+/// `guild.roles` and `guild.emojis` are always empty, those live in their own maps.
+#[derive(Clone, Debug)]
+pub struct CachedGuild {
+    pub guild: Guild,
+    pub joined_at: Option<iso8601_timestamp::Timestamp>,
+    pub large: bool,
+    pub member_count: i64,
+    pub unavailable: bool,
+    pub channels: HashSet<ChannelId>,
+    pub threads: HashSet<ChannelId>,
+    pub roles: HashSet<RoleId>,
+    pub emojis: HashSet<EmojiId>,
+    pub members: HashSet<UserId>,
+    pub voice_states: HashSet<UserId>,
+}
+
+impl CachedGuild {
+    fn new(guild: Guild) -> Self {
+        Self {
+            guild,
+            joined_at: None,
+            large: false,
+            member_count: 0,
+            unavailable: false,
+            channels: Default::default(),
+            threads: Default::default(),
+            roles: Default::default(),
+            emojis: Default::default(),
+            members: Default::default(),
+            voice_states: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheStats {
+    pub guilds: usize,
+    pub channels: usize,
+    pub threads: usize,
+    pub roles: usize,
+    pub members: usize,
+    pub users: usize,
+    pub emojis: usize,
+    pub voice_states: usize,
+}
+
+/// Every map is behind its own lock, so reads never wait on unrelated writes.
+/// When more than one lock is needed they are taken in field declaration order.
+#[derive(Default)]
+pub struct Cache {
+    config: CacheConfig,
+    current_user: RwLock<Option<User>>,
+    guilds: RwLock<HashMap<GuildId, CachedGuild>>,
+    channels: RwLock<HashMap<ChannelId, Channel>>,
+    threads: RwLock<HashMap<ChannelId, Channel>>,
+    roles: RwLock<HashMap<RoleId, (GuildId, Role)>>, //the guild is kept for removing them without the guild cached
+    emojis: RwLock<HashMap<EmojiId, (GuildId, Emoji)>>,
+    users: RwLock<HashMap<UserId, User>>,
+    members: RwLock<HashMap<(GuildId, UserId), GuildMember>>,
+    voice_states: RwLock<HashMap<(GuildId, UserId), VoiceState>>,
+}
+
+fn insert_limited<K: Hash + Eq, V>(map: &mut HashMap<K, V>, key: K, value: V, limit: Option<usize>) -> bool {
+    if map.contains_key(&key) || limit.is_none_or(|l| map.len() < l) {
+        map.insert(key, value);
+        true
+    } else {
+        false
+    }
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    fn wants(&self, res: CacheResources) -> bool {
+        self.config.resources.contains(res)
+    }
+
+    pub fn update(&self, event: &GatewayData) {
+        use GatewayData::*;
+
+        match event {
+            Ready(r) => {
+                *self.current_user.write().unwrap() = Some(r.user.clone());
+
+                //READY lists every guild the account is in, anything else (ex. restored from a snapshot) is gone
+                let present: HashSet<_> = r.guilds.iter()
+                    .map(|g| match g {
+                        GatewayGuildCreatePayload::Available(g) => g.guild_info.id,
+                        GatewayGuildCreatePayload::Unavailable(g) => g.id,
+                    })
+                    .collect();
+                for id in self.guild_ids().into_iter().filter(|id| !present.contains(id)) {
+                    self.remove_guild(id);
+                }
+
+                for g in &r.guilds {
+                    self.guild_create(g);
+                }
+            }
+            GuildCreate(g) => self.guild_create(g),
+            GuildUpdate(g) => self.guild_update(g),
+            GuildDelete(g) => {
+                if g.unavailable == Some(true) {
+                    if let Some(cached) = self.guilds.write().unwrap().get_mut(&g.id) {
+                        cached.unavailable = true;
+                    }
+                } else {
+                    self.remove_guild(g.id);
+                }
+            }
+            ChannelCreate(c) | ChannelUpdate(c) => self.upsert_channel(c.guild_id, c),
+            ChannelDelete(c) => self.remove_channel(c.guild_id, c.id),
+            ThreadCreate(t) => self.upsert_thread(&t.thread),
+            ThreadUpdate(t) => self.upsert_thread(t),
+            ThreadDelete(t) => self.remove_channel(Some(t.guild_id), t.id),
+            ThreadListSync(s) => self.thread_list_sync(s),
+            ThreadMemberUpdate(m) => {
+                let mut threads = self.threads.write().unwrap();
+                if let Some(t) = m.member.id.and_then(|id| threads.get_mut(&id)) {
+                    t.member = Some(m.member.clone());
+                }
+            }
+            ThreadMembersUpdate(m) => self.thread_members_update(m),
+            GuildRoleCreate(r) | GuildRoleUpdate(r) => self.upsert_role(r.guild_id, &r.role),
+            GuildRoleDelete(r) if self.wants(CacheResources::ROLES) => {
+                if let Some(g) = self.guilds.write().unwrap().get_mut(&r.guild_id) {
+                    g.roles.remove(&r.role_id);
+                }
+                self.roles.write().unwrap().remove(&r.role_id);
+            }
+            GuildEmojisUpdate(e) => self.replace_emojis(e.guild_id, &e.emojis),
+            GuildMemberAdd(m) => {
+                self.upsert_member(m.guild_id, &m.member);
+                if let Some(g) = self.guilds.write().unwrap().get_mut(&m.guild_id) {
+                    g.member_count += 1;
+                }
+            }
+            GuildMemberUpdate(m) => self.update_member(m),
+            GuildMemberRemove(m) => {
+                if let Some(g) = self.guilds.write().unwrap().get_mut(&m.guild_id) {
+                    g.members.remove(&m.user.id);
+                    g.member_count -= 1;
+                }
+                self.members.write().unwrap().remove(&(m.guild_id, m.user.id));
+            }
+            GuildMembersChunk(c) => {
+                for m in &c.members {
+                    self.upsert_member(c.guild_id, m);
+                }
+            }
+            VoiceStateUpdate(v) => {
+                if let Some(guild_id) = v.guild_id {
+                    self.upsert_voice_state(guild_id, v);
+                }
+            }
+            MessageCreate(m) | MessageUpdate(m) => {
+                if let Some(author) = &m.rest.author {
+                    self.upsert_user(author);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    fn guild_create(&self, payload: &GatewayGuildCreatePayload) {
+        let g = match payload {
+            GatewayGuildCreatePayload::Available(g) => g,
+            GatewayGuildCreatePayload::Unavailable(g) => {
+                if let Some(cached) = self.guilds.write().unwrap().get_mut(&g.id) {
+                    cached.unavailable = true;
+                }
+                return;
+            }
+        };
+        let id = g.guild_info.id;
+
+        //GUILD_CREATE carries the full state, so whatever was cached before is stale
+        self.remove_guild(id);
+
+        if self.wants(CacheResources::GUILDS) {
+            let mut info = g.guild_info.clone();
+            info.roles = vec![];
+            info.emojis = vec![];
+            let mut cached = CachedGuild::new(info);
+            cached.joined_at = g.joined_at;
+            cached.large = g.large;
+            cached.member_count = g.member_count;
+            insert_limited(&mut self.guilds.write().unwrap(), id, cached, self.config.max_guilds);
+        }
+
+        for c in &g.channels {
+            let mut c = c.clone();
+            c.guild_id = Some(id);
+            self.upsert_channel(Some(id), &c);
+        }
+        for t in &g.threads {
+            let mut t = t.clone();
+            t.guild_id = Some(id);
+            self.upsert_channel(Some(id), &t);
+        }
+        for r in &g.guild_info.roles {
+            self.upsert_role(id, r);
+        }
+        self.replace_emojis(id, &g.guild_info.emojis);
+        for m in &g.members {
+            self.upsert_member(id, m);
+        }
+        for v in &g.voice_states {
+            self.upsert_voice_state(id, v);
+        }
+    }
+
+    fn guild_update(&self, guild: &Guild) {
+        {
+            let mut guilds = self.guilds.write().unwrap();
+            if let Some(cached) = guilds.get_mut(&guild.id) {
+                let mut info = guild.clone();
+                info.roles = vec![];
+                info.emojis = vec![];
+                cached.guild = info;
+            }
+        }
+        for r in &guild.roles {
+            self.upsert_role(guild.id, r);
+        }
+        self.replace_emojis(guild.id, &guild.emojis);
+    }
+
+    fn remove_guild(&self, id: GuildId) {
+        let Some(g) = self.guilds.write().unwrap().remove(&id) else {
+            //not tracked (or guilds aren't cached), fall back to a full scan
+            self.channels.write().unwrap().retain(|_, c| c.guild_id != Some(id));
+            self.threads.write().unwrap().retain(|_, c| c.guild_id != Some(id));
+            self.roles.write().unwrap().retain(|_, (g, _)| *g != id);
+            self.emojis.write().unwrap().retain(|_, (g, _)| *g != id);
+            self.members.write().unwrap().retain(|(g, _), _| *g != id);
+            self.voice_states.write().unwrap().retain(|(g, _), _| *g != id);
+            return;
+        };
+
+        let mut channels = self.channels.write().unwrap();
+        g.channels.iter().for_each(|c| { channels.remove(c); });
+        drop(channels);
+        let mut threads = self.threads.write().unwrap();
+        g.threads.iter().for_each(|c| { threads.remove(c); });
+        drop(threads);
+        let mut roles = self.roles.write().unwrap();
+        g.roles.iter().for_each(|r| { roles.remove(r); });
+        drop(roles);
+        let mut emojis = self.emojis.write().unwrap();
+        g.emojis.iter().for_each(|e| { emojis.remove(e); });
+        drop(emojis);
+        let mut members = self.members.write().unwrap();
+        g.members.into_iter().for_each(|u| { members.remove(&(id, u)); });
+        drop(members);
+        let mut voice_states = self.voice_states.write().unwrap();
+        g.voice_states.into_iter().for_each(|u| { voice_states.remove(&(id, u)); });
+    }
+
+    fn is_thread(c: &Channel) -> bool {
+        c.thread_metadata.is_some()
+    }
+
+    fn upsert_channel(&self, guild_id: Option<GuildId>, c: &Channel) {
+        let (wanted, map, limit) = if Self::is_thread(c) {
+            (CacheResources::THREADS, &self.threads, self.config.max_threads)
+        } else {
+            (CacheResources::CHANNELS, &self.channels, self.config.max_channels)
+        };
+        if !self.wants(wanted) {
+            return;
+        }
+
+        let mut guilds = self.guilds.write().unwrap();
+        if insert_limited(&mut map.write().unwrap(), c.id, c.clone(), limit) {
+            if let Some(g) = guild_id.and_then(|id| guilds.get_mut(&id)) {
+                if Self::is_thread(c) {
+                    g.threads.insert(c.id);
+                } else {
+                    g.channels.insert(c.id);
+                }
+            }
+        }
+    }
+
+    fn remove_channel(&self, guild_id: Option<GuildId>, id: ChannelId) {
+        if let Some(guild_id) = guild_id {
+            if let Some(g) = self.guilds.write().unwrap().get_mut(&guild_id) {
+                g.channels.remove(&id);
+                g.threads.remove(&id);
+            }
+        }
+        self.channels.write().unwrap().remove(&id);
+        self.threads.write().unwrap().remove(&id);
+    }
+
+    /// Only active threads are kept, archived ones are dropped.
+    fn upsert_thread(&self, t: &Channel) {
+        if t.thread_metadata.as_ref().is_some_and(|m| m.archived) {
+            self.remove_channel(t.guild_id, t.id);
+            return;
+        }
+
+        let mut t = t.clone();
+        if t.member.is_none() {
+            //THREAD_UPDATE doesn't carry the current user's membership
+            t.member = self.thread(t.id).and_then(|old| old.member);
+        }
+        self.upsert_channel(t.guild_id, &t);
+    }
+
+    fn thread_list_sync(&self, s: &GatewayThreadListSyncPayload) {
+        //threads of the synced parents missing from the list were archived or are no longer visible
+        let stale: Vec<_> = self.threads.read().unwrap().values()
+            .filter(|t| t.guild_id == Some(s.guild_id))
+            .filter(|t| s.channel_ids.as_ref().is_none_or(|ids| t.parent_id.is_some_and(|p| ids.contains(&p))))
+            .map(|t| t.id)
+            .collect();
+        for id in stale {
+            self.remove_channel(Some(s.guild_id), id);
+        }
+
+        for t in &s.threads {
+            let mut t = t.clone();
+            t.guild_id = Some(s.guild_id);
+            t.member = s.members.iter().find(|m| m.id == Some(t.id)).cloned();
+            self.upsert_thread(&t);
+        }
+    }
+
+    fn thread_members_update(&self, u: &GatewayThreadMembersUpdatePayload) {
+        let me = self.current_user().map(|u| u.id);
+        let mut threads = self.threads.write().unwrap();
+        let Some(t) = threads.get_mut(&u.id) else {
+            return;
+        };
+
+        t.member_count = Some(u.member_count);
+        let Some(me) = me else {
+            return;
+        };
+        if let Some(m) = u.added_members.iter().flatten().find(|m| m.user_id == Some(me)) {
+            t.member = Some(m.clone());
+        }
+        if u.removed_member_ids.iter().flatten().any(|id| *id == me) {
+            t.member = None;
+        }
+    }
+
+    fn upsert_role(&self, guild_id: GuildId, r: &Role) {
+        if !self.wants(CacheResources::ROLES) {
+            return;
+        }
+
+        let mut guilds = self.guilds.write().unwrap();
+        if insert_limited(&mut self.roles.write().unwrap(), r.id, (guild_id, r.clone()), self.config.max_roles) {
+            if let Some(g) = guilds.get_mut(&guild_id) {
+                g.roles.insert(r.id);
+            }
+        }
+    }
+
+    fn replace_emojis(&self, guild_id: GuildId, new: &[Emoji]) {
+        if !self.wants(CacheResources::EMOJIS) {
+            return;
+        }
+
+        let mut guilds = self.guilds.write().unwrap();
+        let mut emojis = self.emojis.write().unwrap();
+        match guilds.get_mut(&guild_id) {
+            Some(g) => g.emojis.drain().for_each(|e| { emojis.remove(&e); }),
+            None => emojis.retain(|_, (g, _)| *g != guild_id),
+        }
+        for e in new {
+            let Some(id) = e.id else { continue };
+            if insert_limited(&mut emojis, id, (guild_id, e.clone()), self.config.max_emojis) {
+                if let Some(g) = guilds.get_mut(&guild_id) {
+                    g.emojis.insert(id);
+                }
+            }
+        }
+    }
+
+    fn upsert_user(&self, u: &User) {
+        if self.wants(CacheResources::USERS) {
+            insert_limited(&mut self.users.write().unwrap(), u.id, u.clone(), self.config.max_users);
+        }
+    }
+
+    fn upsert_member(&self, guild_id: GuildId, m: &GuildMember) {
+        let Some(user) = &m.user else { return };
+        self.upsert_user(user);
+
+        if !self.wants(CacheResources::MEMBERS) {
+            return;
+        }
+
+        let mut guilds = self.guilds.write().unwrap();
+        let key = (guild_id, user.id);
+        if insert_limited(&mut self.members.write().unwrap(), key, m.clone(), self.config.max_members) {
+            if let Some(g) = guilds.get_mut(&guild_id) {
+                g.members.insert(user.id);
+            }
+        }
+    }
+
+    fn update_member(&self, m: &GatewayGuildMemberUpdatePayload) {
+        self.upsert_user(&m.user);
+
+        let mut members = self.members.write().unwrap();
+        let Some(cached) = members.get_mut(&(m.guild_id, m.user.id)) else {
+            return;
+        };
+        cached.user = Some(m.user.clone());
+        cached.roles = m.roles.clone();
+        cached.nick = m.nick.clone();
+        cached.avatar = m.avatar.clone();
+        cached.premium_since = m.premium_since;
+        cached.communication_disabled_until = m.communication_disabled_until;
+        if let Some(joined_at) = m.joined_at {
+            cached.joined_at = joined_at;
+        }
+        if let Some(deaf) = m.deaf {
+            cached.deaf = deaf;
+        }
+        if let Some(mute) = m.mute {
+            cached.mute = mute;
+        }
+    }
+
+    fn upsert_voice_state(&self, guild_id: GuildId, v: &VoiceState) {
+        if !self.wants(CacheResources::VOICE_STATES) {
+            return;
+        }
+
+        let mut guilds = self.guilds.write().unwrap();
+        let mut voice_states = self.voice_states.write().unwrap();
+        let v_user_id = v.user_id;
+        let key = (guild_id, v_user_id);
+        if v.channel_id.is_none() {
+            //user left voice
+            voice_states.remove(&key);
+            if let Some(g) = guilds.get_mut(&guild_id) {
+                g.voice_states.remove(&v.user_id);
+            }
+            return;
+        }
+
+        let mut v = v.clone();
+        v.guild_id = Some(guild_id);
+        if insert_limited(&mut voice_states, key, v, self.config.max_voice_states) {
+            if let Some(g) = guilds.get_mut(&guild_id) {
+                g.voice_states.insert(v_user_id);
+            }
+        }
+    }
+
+    pub fn current_user(&self) -> Option<User> {
+        self.current_user.read().unwrap().clone()
+    }
+
+    pub fn guild(&self, id: GuildId) -> Option<CachedGuild> {
+        self.guilds.read().unwrap().get(&id).cloned()
+    }
+
+    /// Runs `f` on the cached guild without cloning it.
+    pub fn with_guild<R>(&self, id: GuildId, f: impl FnOnce(&CachedGuild) -> R) -> Option<R> {
+        self.guilds.read().unwrap().get(&id).map(f)
+    }
+
+    pub fn guild_ids(&self) -> Vec<GuildId> {
+        self.guilds.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Looks up a channel, falling back to threads.
+    pub fn channel(&self, id: ChannelId) -> Option<Channel> {
+        self.channels.read().unwrap().get(&id).cloned()
+            .or_else(|| self.thread(id))
+    }
+
+    pub fn thread(&self, id: ChannelId) -> Option<Channel> {
+        self.threads.read().unwrap().get(&id).cloned()
+    }
+
+    pub fn role(&self, id: RoleId) -> Option<Role> {
+        self.roles.read().unwrap().get(&id).map(|(_, r)| r.clone())
+    }
+
+    pub fn emoji(&self, id: EmojiId) -> Option<Emoji> {
+        self.emojis.read().unwrap().get(&id).map(|(_, e)| e.clone())
+    }
+
+    pub fn user(&self, id: UserId) -> Option<User> {
+        self.users.read().unwrap().get(&id).cloned()
+    }
+
+    pub fn member(&self, guild_id: GuildId, user_id: UserId) -> Option<GuildMember> {
+        self.members.read().unwrap().get(&(guild_id, user_id)).cloned()
+    }
+
+    pub fn voice_state(&self, guild_id: GuildId, user_id: UserId) -> Option<VoiceState> {
+        self.voice_states.read().unwrap().get(&(guild_id, user_id)).cloned()
+    }
+
+    pub fn guild_channels(&self, guild_id: GuildId) -> Vec<Channel> {
+        let ids = self.with_guild(guild_id, |g| g.channels.clone()).unwrap_or_default();
+        let channels = self.channels.read().unwrap();
+        ids.iter().filter_map(|id| channels.get(id).cloned()).collect()
+    }
+
+    /// Active threads of a guild.
+    pub fn guild_threads(&self, guild_id: GuildId) -> Vec<Channel> {
+        let ids = self.with_guild(guild_id, |g| g.threads.clone()).unwrap_or_default();
+        let threads = self.threads.read().unwrap();
+        ids.iter().filter_map(|id| threads.get(id).cloned()).collect()
+    }
+
+    pub fn guild_roles(&self, guild_id: GuildId) -> Vec<Role> {
+        let ids = self.with_guild(guild_id, |g| g.roles.clone()).unwrap_or_default();
+        let roles = self.roles.read().unwrap();
+        ids.iter().filter_map(|id| roles.get(id).map(|(_, r)| r.clone())).collect()
+    }
+
+    pub fn guild_emojis(&self, guild_id: GuildId) -> Vec<Emoji> {
+        let ids = self.with_guild(guild_id, |g| g.emojis.clone()).unwrap_or_default();
+        let emojis = self.emojis.read().unwrap();
+        ids.iter().filter_map(|id| emojis.get(id).map(|(_, e)| e.clone())).collect()
+    }
+
+    pub fn guild_members(&self, guild_id: GuildId) -> Vec<GuildMember> {
+        let ids = self.with_guild(guild_id, |g| g.members.clone()).unwrap_or_default();
+        let members = self.members.read().unwrap();
+        ids.into_iter().filter_map(|u| members.get(&(guild_id, u)).cloned()).collect()
+    }
+
+    /// Guild-wide permissions of a member, needs the guild, its roles and the member cached.
+    pub fn base_permissions(&self, guild_id: GuildId, user_id: UserId) -> Option<Permissions> {
+        let guild = self.with_guild(guild_id, |g| g.guild.clone())?;
+        let member = self.member(guild_id, user_id)?;
+        let roles = self.guild_roles(guild_id);
+        Some(PermissionCalculator::for_member(&guild, &roles, &member)?.base())
+    }
+
+    /// Permissions of a member in a guild channel or thread, needs the channel (and the parent for threads) cached as well.
+    pub fn channel_permissions(&self, channel_id: ChannelId, user_id: UserId) -> Option<Permissions> {
+        let channel = self.channel(channel_id)?;
+        let guild_id = channel.guild_id?;
+        let guild = self.with_guild(guild_id, |g| g.guild.clone())?;
+        let member = self.member(guild_id, user_id)?;
+        let roles = self.guild_roles(guild_id);
+        let parent = channel.parent_id.and_then(|p| self.channel(p));
+        Some(PermissionCalculator::for_member(&guild, &roles, &member)?.in_channel(&channel, parent.as_ref()))
+    }
+
+    /// Guild, channel, role and member state, ready to be saved to disk.
+    pub fn snapshot(&self) -> CacheSnapshot {
+        let guilds = self.guilds.read().unwrap().values().cloned().collect::<Vec<_>>();
+
+        CacheSnapshot {
+            version: CacheSnapshot::VERSION,
+            user: self.current_user(),
+            guilds: guilds.into_iter()
+                .map(|g| GuildSnapshot {
+                    channels: self.guild_channels(g.guild.id),
+                    roles: self.guild_roles(g.guild.id),
+                    members: self.guild_members(g.guild.id),
+                    guild: g.guild,
+                    joined_at: g.joined_at,
+                    large: g.large,
+                    member_count: g.member_count,
+                })
+                .collect(),
+        }
+    }
+
+    /// Loads a snapshot on top of the current state. Restored guilds are treated as unavailable
+    /// until their GUILD_CREATE arrives and replaces them.
+    pub fn restore(&self, snapshot: CacheSnapshot) {
+        if let Some(user) = snapshot.user {
+            self.current_user.write().unwrap().get_or_insert(user);
+        }
+
+        for g in snapshot.guilds {
+            let id = g.guild.id;
+            self.remove_guild(id);
+
+            if self.wants(CacheResources::GUILDS) {
+                let mut cached = CachedGuild::new(g.guild);
+                cached.joined_at = g.joined_at;
+                cached.large = g.large;
+                cached.member_count = g.member_count;
+                cached.unavailable = true;
+                insert_limited(&mut self.guilds.write().unwrap(), id, cached, self.config.max_guilds);
+            }
+
+            for c in &g.channels {
+                self.upsert_channel(Some(id), c);
+            }
+            for r in &g.roles {
+                self.upsert_role(id, r);
+            }
+            for m in &g.members {
+                self.upsert_member(id, m);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            guilds: self.guilds.read().unwrap().len(),
+            channels: self.channels.read().unwrap().len(),
+            threads: self.threads.read().unwrap().len(),
+            roles: self.roles.read().unwrap().len(),
+            members: self.members.read().unwrap().len(),
+            users: self.users.read().unwrap().len(),
+            emojis: self.emojis.read().unwrap().len(),
+            voice_states: self.voice_states.read().unwrap().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::gateway::fake_types::GatewayEvent;
+
+    fn dispatch(t: &str, d: Value) -> GatewayData {
+        let event: GatewayEvent = serde_json::from_str(&json!({ "op": 0, "s": 1, "t": t, "d": d }).to_string()).unwrap();
+        event.d.expect("dispatch should parse")
+    }
+
+    fn role(id: &str) -> Value {
+        json!({
+            "id": id, "name": "role", "color": 0, "hoist": false, "icon": null, "unicode_emoji": null,
+            "position": 0, "permissions": "0", "managed": false, "mentionable": false, "flags": 0
+        })
+    }
+
+    fn cache(resources: CacheResources) -> Cache {
+        Cache::new(CacheConfigBuilder::default().resources(resources).build().unwrap())
+    }
+
+    fn thread(id: u64, parent_id: u64, archived: bool) -> Value {
+        json!({
+            "id": id.to_string(), "type": 11, "guild_id": "1", "parent_id": parent_id.to_string(),
+            "thread_metadata": {
+                "archived": archived, "auto_archive_duration": 60, "locked": false,
+                "archive_timestamp": "2023-01-01T00:00:00Z"
+            }
+        })
+    }
+
+    fn thread_member(thread_id: u64, user_id: u64) -> Value {
+        json!({ "id": thread_id.to_string(), "user_id": user_id.to_string(), "join_timestamp": "2023-01-01T00:00:00Z", "flags": 0 })
+    }
+
+    fn member_of(cache: &Cache, thread_id: u64) -> Option<u64> {
+        cache.thread(ChannelId::from(thread_id)).unwrap().member.and_then(|m| m.user_id).map(|u| u.get())
+    }
+
+    #[test]
+    fn untracked_guilds_drop_their_roles_and_emojis() {
+        let cache = cache(CacheResources::ROLES | CacheResources::EMOJIS);
+        cache.update(&dispatch("GUILD_ROLE_CREATE", json!({ "guild_id": "1", "role": role("10") })));
+        cache.update(&dispatch("GUILD_ROLE_CREATE", json!({ "guild_id": "2", "role": role("20") })));
+        cache.update(&dispatch("GUILD_EMOJIS_UPDATE", json!({ "guild_id": "1", "emojis": [{ "id": "11", "name": "a" }] })));
+        cache.update(&dispatch("GUILD_EMOJIS_UPDATE", json!({ "guild_id": "2", "emojis": [{ "id": "21", "name": "b" }] })));
+
+        //an update of an untracked guild still replaces its old emojis
+        cache.update(&dispatch("GUILD_EMOJIS_UPDATE", json!({ "guild_id": "1", "emojis": [{ "id": "12", "name": "c" }] })));
+        assert!(cache.emoji(EmojiId::from(11)).is_none());
+        assert!(cache.emoji(EmojiId::from(12)).is_some());
+
+        cache.update(&dispatch("GUILD_DELETE", json!({ "id": "1" })));
+        assert!(cache.role(RoleId::from(10)).is_none());
+        assert!(cache.emoji(EmojiId::from(12)).is_none());
+        assert!(cache.role(RoleId::from(20)).is_some());
+        assert!(cache.emoji(EmojiId::from(21)).is_some());
+    }
+
+    #[test]
+    fn threads_are_upserted_and_removed() {
+        let cache = cache(CacheResources::THREADS);
+        let mut created = thread(100, 10, false);
+        created["member"] = thread_member(100, 5);
+        cache.update(&dispatch("THREAD_CREATE", created));
+        assert_eq!(member_of(&cache, 100), Some(5));
+
+        //updates don't carry the membership, the cached one is kept
+        let mut updated = thread(100, 10, false);
+        updated["name"] = json!("renamed");
+        cache.update(&dispatch("THREAD_UPDATE", updated));
+        assert_eq!(cache.thread(ChannelId::from(100)).unwrap().name.as_deref(), Some("renamed"));
+        assert_eq!(member_of(&cache, 100), Some(5));
+
+        cache.update(&dispatch("THREAD_UPDATE", thread(100, 10, true)));
+        assert!(cache.thread(ChannelId::from(100)).is_none());
+
+        cache.update(&dispatch("THREAD_CREATE", thread(101, 10, false)));
+        cache.update(&dispatch("THREAD_DELETE", json!({ "id": "101", "guild_id": "1", "parent_id": "10", "type": 11 })));
+        assert_eq!(cache.stats().threads, 0);
+    }
+
+    #[test]
+    fn list_syncs_replace_only_the_listed_channels() {
+        let cache = cache(CacheResources::THREADS);
+        cache.update(&dispatch("THREAD_CREATE", thread(100, 10, false)));
+        cache.update(&dispatch("THREAD_CREATE", thread(200, 20, false)));
+
+        cache.update(&dispatch("THREAD_LIST_SYNC", json!({
+            "guild_id": "1",
+            "channel_ids": ["10"],
+            "threads": [thread(101, 10, false)],
+            "members": [thread_member(101, 5)],
+        })));
+        assert!(cache.thread(ChannelId::from(100)).is_none());
+        assert!(cache.thread(ChannelId::from(200)).is_some());
+        assert_eq!(member_of(&cache, 101), Some(5));
+
+        //without channel_ids the whole guild is synced
+        cache.update(&dispatch("THREAD_LIST_SYNC", json!({ "guild_id": "1", "threads": [], "members": [] })));
+        assert_eq!(cache.stats().threads, 0);
+    }
+
+    #[test]
+    fn member_updates_track_the_current_user() {
+        let cache = cache(CacheResources::THREADS);
+        *cache.current_user.write().unwrap() = Some(serde_json::from_value(json!({ "id": "5", "username": "me", "discriminator": "0" })).unwrap());
+        cache.update(&dispatch("THREAD_CREATE", thread(100, 10, false)));
+
+        let update = |added: Value, removed: Value, count: i32| dispatch("THREAD_MEMBERS_UPDATE", json!({
+            "id": "100", "guild_id": "1", "member_count": count, "added_members": added, "removed_member_ids": removed,
+        }));
+        cache.update(&update(json!([thread_member(100, 6)]), Value::Null, 1));
+        assert_eq!(member_of(&cache, 100), None);
+        assert_eq!(cache.thread(ChannelId::from(100)).unwrap().member_count, Some(1));
+
+        cache.update(&update(json!([thread_member(100, 5)]), Value::Null, 2));
+        assert_eq!(member_of(&cache, 100), Some(5));
+
+        cache.update(&update(Value::Null, json!(["6"]), 1));
+        assert_eq!(member_of(&cache, 100), Some(5));
+
+        cache.update(&update(Value::Null, json!(["5"]), 0));
+        assert_eq!(member_of(&cache, 100), None);
+        assert_eq!(cache.thread(ChannelId::from(100)).unwrap().member_count, Some(0));
+    }
+}
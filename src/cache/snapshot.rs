@@ -0,0 +1,51 @@
+use std::{io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dapi::routes::v10::types::{Channel, Guild, GuildMember, Role, User};
+
+/// On-disk form of a guild and the state belonging to it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuildSnapshot {
+    pub guild: Guild,
+    pub joined_at: Option<iso8601_timestamp::Timestamp>,
+    pub large: bool,
+    pub member_count: i64,
+    pub channels: Vec<Channel>,
+    pub roles: Vec<Role>,
+    pub members: Vec<GuildMember>,
+}
+
+/// Guild, channel, role and member state of one account, restored at startup so names resolve
+/// before READY arrives. Restored guilds are replaced by their GUILD_CREATE, and evicted on READY if
+/// the account is no longer in them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CacheSnapshot {
+    pub version: u32,
+    pub user: Option<User>,
+    pub guilds: Vec<GuildSnapshot>,
+}
+
+impl CacheSnapshot {
+    pub const VERSION: u32 = 1;
+
+    pub async fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = tokio::fs::read(path).await?;
+        let snapshot: Self = serde_json::from_slice(&data)?;
+        if snapshot.version != Self::VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported snapshot version {} (expected {})", snapshot.version, Self::VERSION),
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Writes to a temporary file first, so a crash mid-write never leaves a truncated snapshot behind.
+    pub async fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(self)?).await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+}
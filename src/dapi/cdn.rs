@@ -0,0 +1,227 @@
+#![allow(unused)]
+//urls of images and other assets on discord's cdn, built from the ids and hashes the api returns
+
+use std::fmt;
+
+use super::routes::common_types::{EmojiId, GuildId, RoleId, StickerId, UserId};
+
+pub const CDN_BASE: &str = "https://cdn.discordapp.com";
+/// Serves what the cdn doesn't, gif stickers.
+pub const MEDIA_BASE: &str = "https://media.discordapp.net";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Gif,
+    /// Only for lottie stickers.
+    Lottie,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::WebP => "webp",
+            Self::Gif => "gif",
+            Self::Lottie => "json",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CdnError {
+    /// Sizes have to be a power of 2 between 16 and 4096.
+    InvalidSize(u16),
+    UnsupportedFormat(ImageFormat),
+}
+
+impl fmt::Display for CdnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(f, "{size} isn't a valid cdn image size, it has to be a power of 2 between 16 and 4096"),
+            Self::UnsupportedFormat(format) => write!(f, "the asset isn't available as {format:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CdnError {}
+
+const IMAGE: &[ImageFormat] = &[ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP, ImageFormat::Gif];
+
+/// An asset on the cdn. Animated ones (hashes starting with `a_`, animated emojis) are gifs by default, the rest pngs.
+/// `.format(...)` and `.size(...)` are checked when the url is built:
+/// `cdn::user_avatar(user.id, &hash).format(ImageFormat::WebP).size(256).url()?`
+#[derive(Clone, Debug)]
+pub struct CdnUrl {
+    base: &'static str,
+    path: String,
+    animated: bool,
+    formats: &'static [ImageFormat],
+    format: Option<ImageFormat>,
+    size: Option<u16>,
+}
+
+impl CdnUrl {
+    fn new(path: String, animated: bool, formats: &'static [ImageFormat]) -> Self {
+        Self {
+            base: CDN_BASE,
+            path,
+            animated,
+            formats,
+            format: None,
+            size: None,
+        }
+    }
+
+    fn image(path: String, hash: &str) -> Self {
+        Self::new(format!("{path}/{hash}"), hash.starts_with("a_"), IMAGE)
+    }
+
+    pub fn format(mut self, format: ImageFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn size(mut self, size: u16) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.animated
+    }
+
+    pub fn url(&self) -> Result<String, CdnError> {
+        let format = self.format.unwrap_or(if self.animated && self.formats.contains(&ImageFormat::Gif) {
+            ImageFormat::Gif
+        } else {
+            self.formats[0]
+        });
+        //gifs of still images don't exist
+        if !self.formats.contains(&format) || (format == ImageFormat::Gif && !self.animated) {
+            return Err(CdnError::UnsupportedFormat(format));
+        }
+
+        let mut url = format!("{}/{}.{}", self.base, self.path, format.extension());
+        let mut query = vec![];
+        if let Some(size) = self.size {
+            if !(16..=4096).contains(&size) || !size.is_power_of_two() {
+                return Err(CdnError::InvalidSize(size));
+            }
+            query.push(format!("size={size}"));
+        }
+        if format == ImageFormat::WebP && self.animated {
+            query.push("animated=true".to_owned());
+        }
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+        Ok(url)
+    }
+}
+
+pub fn user_avatar(user_id: UserId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("avatars/{user_id}"), hash)
+}
+
+/// The avatar of users without one. Users with the new unique usernames (discriminator "0") get it by id, the rest by discriminator.
+pub fn default_avatar(user_id: UserId, discriminator: &str) -> CdnUrl {
+    let index = match discriminator.parse::<u64>() {
+        Ok(d) if d != 0 => d % 5,
+        _ => (u64::from(user_id) >> 22) % 6,
+    };
+    CdnUrl::new(format!("embed/avatars/{index}"), false, &[ImageFormat::Png])
+}
+
+/// A guild specific avatar, see `GuildMember.avatar`.
+pub fn guild_member_avatar(guild_id: GuildId, user_id: UserId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("guilds/{guild_id}/users/{user_id}/avatars"), hash)
+}
+
+pub fn user_banner(user_id: UserId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("banners/{user_id}"), hash)
+}
+
+pub fn guild_icon(guild_id: GuildId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("icons/{guild_id}"), hash)
+}
+
+pub fn guild_splash(guild_id: GuildId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("splashes/{guild_id}"), hash)
+}
+
+pub fn guild_discovery_splash(guild_id: GuildId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("discovery-splashes/{guild_id}"), hash)
+}
+
+pub fn guild_banner(guild_id: GuildId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("banners/{guild_id}"), hash)
+}
+
+pub fn emoji(emoji_id: EmojiId, animated: bool) -> CdnUrl {
+    CdnUrl::new(format!("emojis/{emoji_id}"), animated, IMAGE)
+}
+
+/// `format_type` as in `StickerItem`, stickers only come in the format they were uploaded in.
+pub fn sticker(sticker_id: StickerId, format_type: u32) -> CdnUrl {
+    let formats: &'static [ImageFormat] = match format_type {
+        3 => &[ImageFormat::Lottie],
+        4 => &[ImageFormat::Gif],
+        _ => &[ImageFormat::Png], //png and apng
+    };
+    let mut url = CdnUrl::new(format!("stickers/{sticker_id}"), format_type == 4, formats);
+    if format_type == 4 {
+        url.base = MEDIA_BASE;
+    }
+    url
+}
+
+pub fn role_icon(role_id: RoleId, hash: &str) -> CdnUrl {
+    CdnUrl::image(format!("role-icons/{role_id}"), hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animated_hashes_default_to_gifs() {
+        let user = UserId::new(80351110224678912);
+        assert_eq!(user_avatar(user, "abc").url().unwrap(), "https://cdn.discordapp.com/avatars/80351110224678912/abc.png");
+        assert_eq!(user_avatar(user, "a_abc").url().unwrap(), "https://cdn.discordapp.com/avatars/80351110224678912/a_abc.gif");
+        assert_eq!(
+            user_avatar(user, "a_abc").format(ImageFormat::WebP).size(256).url().unwrap(),
+            "https://cdn.discordapp.com/avatars/80351110224678912/a_abc.webp?size=256&animated=true"
+        );
+    }
+
+    #[test]
+    fn formats_and_sizes_are_checked() {
+        let guild = GuildId::new(1);
+        assert_eq!(guild_icon(guild, "abc").format(ImageFormat::Gif).url(), Err(CdnError::UnsupportedFormat(ImageFormat::Gif)));
+        assert_eq!(guild_icon(guild, "abc").format(ImageFormat::Lottie).url(), Err(CdnError::UnsupportedFormat(ImageFormat::Lottie)));
+        assert_eq!(guild_icon(guild, "abc").size(100).url(), Err(CdnError::InvalidSize(100)));
+        assert_eq!(guild_icon(guild, "abc").size(8192).url(), Err(CdnError::InvalidSize(8192)));
+        assert_eq!(guild_icon(guild, "abc").size(4096).url().unwrap(), "https://cdn.discordapp.com/icons/1/abc.png?size=4096");
+    }
+
+    #[test]
+    fn default_avatars() {
+        let user = UserId::new(80351110224678912);
+        assert_eq!(default_avatar(user, "1337").url().unwrap(), "https://cdn.discordapp.com/embed/avatars/2.png");
+        assert_eq!(default_avatar(user, "0").url().unwrap(), format!("https://cdn.discordapp.com/embed/avatars/{}.png", (80351110224678912u64 >> 22) % 6));
+    }
+
+    #[test]
+    fn stickers_come_in_their_own_format() {
+        let sticker_id = StickerId::new(1);
+        assert_eq!(sticker(sticker_id, 1).url().unwrap(), "https://cdn.discordapp.com/stickers/1.png");
+        assert_eq!(sticker(sticker_id, 3).url().unwrap(), "https://cdn.discordapp.com/stickers/1.json");
+        assert_eq!(sticker(sticker_id, 4).url().unwrap(), "https://media.discordapp.net/stickers/1.gif");
+        assert!(sticker(sticker_id, 1).format(ImageFormat::WebP).url().is_err());
+    }
+}
@@ -0,0 +1,223 @@
+#![allow(unused)]
+//an in-memory transport for driving DApi without a network
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::future::BoxFuture;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use serde::Serialize;
+
+use super::transport::{HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind};
+
+/// A scripted response, or a transport failure.
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+    error: Option<TransportErrorKind>,
+}
+
+impl MockResponse {
+    pub fn status(status: u16) -> Self {
+        Self {
+            status: StatusCode::from_u16(status).expect("invalid status code"),
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+            delay: None,
+            error: None,
+        }
+    }
+
+    pub fn json(status: u16, body: impl Serialize) -> Self {
+        let mut resp = Self::status(status).header("content-type", "application/json");
+        resp.body = serde_json::to_vec(&body).expect("unserializable mock body");
+        resp
+    }
+
+    pub fn ok(body: impl Serialize) -> Self {
+        Self::json(200, body)
+    }
+
+    pub fn no_content() -> Self {
+        Self::status(204)
+    }
+
+    /// A 429 the way discord sends them, with the body and the Retry-After header.
+    pub fn rate_limited(retry_after: f64, global: bool) -> Self {
+        let resp = Self::json(429, serde_json::json!({
+            "message": "You are being rate limited.",
+            "retry_after": retry_after,
+            "global": global,
+        }))
+        .header("retry-after", retry_after.ceil().to_string());
+
+        if global {
+            resp.header("x-ratelimit-global", "true").header("x-ratelimit-scope", "global")
+        } else {
+            resp.header("x-ratelimit-scope", "user")
+        }
+    }
+
+    /// The request fails without a response.
+    pub fn error(kind: TransportErrorKind) -> Self {
+        Self {
+            error: Some(kind),
+            ..Self::status(500)
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl AsRef<str>) -> Self {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).expect("invalid header name"),
+            HeaderValue::from_str(value.as_ref()).expect("invalid header value"),
+        );
+        self
+    }
+
+    /// The rate limit headers of a bucket.
+    pub fn bucket(self, hash: &str, limit: u32, remaining: u32, reset_after: f64) -> Self {
+        self.header("x-ratelimit-bucket", hash)
+            .header("x-ratelimit-limit", limit.to_string())
+            .header("x-ratelimit-remaining", remaining.to_string())
+            .header("x-ratelimit-reset-after", reset_after.to_string())
+    }
+
+    /// Responds only after `delay`, for exercising timeouts.
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    fallback: Option<MockResponse>,
+    expected_headers: Vec<(HeaderName, Option<String>)>,
+    requests: Vec<HttpRequest>,
+}
+
+/// Answers requests with scripted responses, in the order they were pushed, and records every request.
+/// Clones share the script, so one can be handed to `DApi::with_transport` and the other kept for checking:
+/// ```ignore
+/// let mock = MockTransport::new();
+/// mock.push(MockResponse::rate_limited(0.5, false)).push(MockResponse::ok(user));
+/// let dapi = DApi::<v10>::with_transport(mock.clone());
+/// dapi.get(&users_me()).await?;
+/// assert_eq!(mock.requests().len(), 2);
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, resp: MockResponse) -> &Self {
+        self.state.lock().unwrap().responses.push_back(resp);
+        self
+    }
+
+    /// Queues `times` 429s in a row.
+    pub fn push_rate_limited(&self, times: usize, retry_after: f64, global: bool) -> &Self {
+        for _ in 0..times {
+            self.push(MockResponse::rate_limited(retry_after, global));
+        }
+        self
+    }
+
+    /// Sent once the scripted responses run out. Without one, requests past the script fail.
+    pub fn fallback(&self, resp: MockResponse) -> &Self {
+        self.state.lock().unwrap().fallback = Some(resp);
+        self
+    }
+
+    /// Every following request has to carry the header with this value, or the mock panics.
+    pub fn expect_header(&self, name: &str, value: impl Into<String>) -> &Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        self.state.lock().unwrap().expected_headers.push((name, Some(value.into())));
+        self
+    }
+
+    /// Every following request must not carry the header, or the mock panics.
+    pub fn expect_no_header(&self, name: &str) -> &Self {
+        let name = HeaderName::from_bytes(name.as_bytes()).expect("invalid header name");
+        self.state.lock().unwrap().expected_headers.push((name, None));
+        self
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn last_request(&self) -> Option<HttpRequest> {
+        self.state.lock().unwrap().requests.last().cloned()
+    }
+
+    /// Scripted responses that haven't been sent yet.
+    pub fn remaining(&self) -> usize {
+        self.state.lock().unwrap().responses.len()
+    }
+}
+
+impl HttpTransport for MockTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            let timeout = req.timeout;
+            let resp = {
+                let mut state = self.state.lock().unwrap();
+                for (name, expected) in &state.expected_headers {
+                    let actual = req.headers.get(name).and_then(|v| v.to_str().ok());
+                    assert_eq!(
+                        actual,
+                        expected.as_deref(),
+                        "header {name} of {} {}",
+                        req.method,
+                        req.url
+                    );
+                }
+
+                let described = format!("{} {}", req.method, req.url);
+                state.requests.push(req);
+                match state.responses.pop_front().or_else(|| state.fallback.clone()) {
+                    Some(r) => r,
+                    None => {
+                        return Err(TransportError::new(
+                            TransportErrorKind::Other,
+                            format!("no response scripted for {described}"),
+                        ))
+                    }
+                }
+            };
+
+            if let Some(delay) = resp.delay {
+                if let Some(timeout) = timeout.filter(|t| *t < delay) {
+                    tokio::time::sleep(timeout).await;
+                    return Err(TransportError::new(TransportErrorKind::Timeout, "mock response timed out"));
+                }
+                tokio::time::sleep(delay).await;
+            }
+
+            match resp.error {
+                Some(kind) => Err(TransportError::new(kind, "mock transport error")),
+                None => Ok(HttpResponse {
+                    status: resp.status,
+                    headers: resp.headers,
+                    body: resp.body,
+                }),
+            }
+        })
+    }
+}
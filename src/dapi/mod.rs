@@ -3,202 +3,598 @@
 use std::{
     collections::HashMap,
     marker::PhantomData,
-    sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc, Mutex,
-    },
+    sync::{Arc, Mutex, Weak},
     time::Duration,
 };
 
-use lazy_regex::regex;
-use reqwest::{Method, StatusCode};
-use serde::{de::DeserializeOwned, Serialize};
-use tokio::{sync::RwLock, time::Instant};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use self::{
+    cdn::CdnUrl,
+    multipart::{FileUpload, MultipartBody},
+    pagination::{Pageable, Paginator},
+    ratelimit::{RateLimitEvent, RateLimitInfo, RateLimiter, Route},
+    request::{DApiRequest, RawRequest, RequestBody, RequestOptions, Response},
+    retry::RetryPolicy,
+    token::Token,
     routes::common_types::DiscordApiError,
-    types::{DApiVersion, Result},
+    transport::{HttpBody, HttpRequest, HttpTransport, ReqwestTransport, TransportError, TransportErrorKind},
+    types::{DApiVersion, Result, URL_COMPONENT},
 };
 
+pub mod cdn;
+pub mod mock;
+pub mod multipart;
+pub mod pagination;
+pub mod ratelimit;
+pub mod request;
+pub mod retry;
 pub mod routes;
+pub mod token;
+pub mod transport;
 mod types;
 pub mod versions;
 
-pub use self::types::{DApiDELETE, DApiError, DApiGET, DApiPATCH, DApiPOST, DApiPUT};
+pub use self::types::{DApiDELETE, DApiError, DApiGET, DApiPATCH, DApiPOST, DApiPUT, RequestContext};
+
+//discord doesn't document one, cloudflare's ray id is the next best thing
+fn request_id(headers: &HeaderMap) -> Option<String> {
+    ["x-request-id", "cf-ray"]
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(String::from)
+}
+
+//how far a request got, for the context of its error
+#[derive(Default)]
+struct RequestProgress {
+    attempts: u32,
+    last_response: Option<(StatusCode, Option<String>)>,
+}
+
+/// Everything shared by the handles using one token and transport: the transport (and its connection pool) and the rate limits.
+pub struct DApiCore {
+    http: Arc<dyn HttpTransport>,
+    custom_transport: bool,
+    token: Option<Token>,
+    ratelimiter: RateLimiter,
+}
+
+impl DApiCore {
+    /// The core of `token`, shared with every other live handle using it, regardless of their API version.
+    /// Handles with a custom transport only share cores with handles using the very same transport.
+    fn shared(
+        token: Option<Token>,
+        custom_transport: Option<Arc<dyn HttpTransport>>,
+        default_transport: impl FnOnce() -> Result<Arc<dyn HttpTransport>>,
+    ) -> Result<Arc<Self>> {
+        type CoreKey = (Option<String>, Option<usize>); //token, custom transport
+        static CORES: Lazy<Mutex<HashMap<CoreKey, Weak<DApiCore>>>> = Lazy::new(Default::default);
 
+        let mut cores = CORES.lock().unwrap();
+        cores.retain(|_, c| c.strong_count() > 0);
+
+        //custom transports are told apart by their address
+        let key = (token.as_ref().map(Token::authorization), custom_transport.as_ref().map(|h| Arc::as_ptr(h) as *const () as usize));
+        if let Some(core) = cores.get(&key).and_then(Weak::upgrade) {
+            return Ok(core);
+        }
+
+        let core = Arc::new(Self {
+            custom_transport: custom_transport.is_some(),
+            http: match custom_transport {
+                Some(h) => h,
+                None => default_transport()?,
+            },
+            token,
+            ratelimiter: RateLimiter::new(),
+        });
+        cores.insert(key, Arc::downgrade(&core));
+        Ok(core)
+    }
+}
+
+/// A cheap to clone handle to the API, handles with the same token share their rate limits.
 pub struct DApi<V: DApiVersion> {
-    http: reqwest::Client,
-    token: Option<String>,
-    user_agent: String,
+    core: Arc<DApiCore>,
+    user_agent: Option<String>,
     api_base: String,
     api_ver: PhantomData<V>,
-    ratelimits: RwLock<HashMap<String, Mutex<Option<Instant>>>>,
-    global_limit: Mutex<Option<Instant>>,
+    retry_policy: RetryPolicy,
+    max_ratelimit_wait: Option<Duration>,
+    timeout: Option<Duration>,
+}
+
+impl<V: DApiVersion> Clone for DApi<V> {
+    fn clone(&self) -> Self {
+        Self {
+            core: Arc::clone(&self.core),
+            user_agent: self.user_agent.clone(),
+            api_base: self.api_base.clone(),
+            api_ver: PhantomData,
+            retry_policy: self.retry_policy.clone(),
+            max_ratelimit_wait: self.max_ratelimit_wait,
+            timeout: self.timeout,
+        }
+    }
 }
 
 impl<V: DApiVersion> DApi<V> {
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            http: reqwest::ClientBuilder::new()
-                .timeout(Duration::from_secs(5))
+        let core = DApiCore::shared(None, None, || {
+            let client = reqwest::ClientBuilder::new()
                 .build()
-                .map_err(DApiError::Instantiation)?,
-            token: None,
-            user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/111.0.0.0 Safari/537.36".to_owned(),
-            api_base: format!("https://discord.com/api/{}", V::VER),
+                .map_err(DApiError::Instantiation)?;
+            Ok(Arc::new(ReqwestTransport::new(client)))
+        })?;
+        Ok(Self::with_core(core))
+    }
+
+    /// A handle sending its requests through `transport` instead of reqwest, e.g. a `MockTransport` in tests.
+    pub fn with_transport(transport: impl HttpTransport + 'static) -> Self {
+        let core = DApiCore::shared(None, Some(Arc::new(transport)), || unreachable!("a transport was given"))
+            .expect("a transport was given");
+        Self::with_core(core)
+    }
+
+    fn with_core(core: Arc<DApiCore>) -> Self {
+        Self {
+            core,
+            user_agent: None,
+            api_base: "https://discord.com/api".to_owned(),
             api_ver: Default::default(),
-            ratelimits: Default::default(),
-            global_limit: Default::default(),
-        })
+            retry_policy: Default::default(),
+            max_ratelimit_wait: None,
+            timeout: Some(Duration::from_secs(5)),
+        }
     }
 
-    pub fn set_token(&mut self, token: impl Into<String>) {
-        self.token = Some(token.into());
+    /// Switches this handle over to the shared core of `token`.
+    /// Strings are taken as user tokens unless they start with `Bot ` or `Bearer `, see `Token`.
+    pub fn set_token(&mut self, token: impl Into<Token>) {
+        let http = self.core.http.clone();
+        let custom = self.core.custom_transport.then(|| http.clone());
+        self.core = DApiCore::shared(Some(token.into()), custom, || Ok(http))
+            .expect("reusing the transport can't fail");
     }
 
+    pub fn token(&self) -> Option<&Token> {
+        self.core.token.as_ref()
+    }
+
+    /// Where requests go, `https://discord.com/api` by default. The API version is appended to it.
+    pub fn set_api_base(&mut self, api_base: impl Into<String>) {
+        self.api_base = api_base.into().trim_end_matches('/').to_owned();
+    }
+
+    /// Overrides the default user agent: `DiscordBot (url, version)` for bot tokens as discord requires,
+    /// a browser's for the rest.
     pub fn set_user_agent(&mut self, user_agent: impl Into<String>) {
-        self.user_agent = user_agent.into();
+        self.user_agent = Some(user_agent.into());
     }
 
-    fn path_into_resource(path: &str) -> Option<&str> {
-        let cap = regex!(r"(?:(?:channels)|(?:guilds)|(?:webhooks))/+(\d{4,21})").captures(path);
-        return if let Some(c) = cap {
-            c.get(1).map(|c| c.as_str())
-        } else {
-            None
-        };
+    pub fn user_agent(&self) -> &str {
+        const BOT: &str = concat!("DiscordBot (https://github.com/Caier/danielek-rs, ", env!("CARGO_PKG_VERSION"), ")");
+        const BROWSER: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/111.0.0.0 Safari/537.36";
+
+        match self.user_agent {
+            Some(ref ua) => ua,
+            None if self.core.token.as_ref().is_some_and(Token::is_bot) => BOT,
+            None => BROWSER,
+        }
+    }
+
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    /// How long requests may take (from connecting until the whole response is read), 5 seconds by default.
+    /// Can be overridden per request with `.timeout(...)`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Requests that would have to wait longer than `max` for a rate limit fail with `DApiError::RateLimited` instead.
+    /// `None` (the default) waits as long as it takes.
+    pub fn set_max_ratelimit_wait(&mut self, max: Option<Duration>) {
+        self.max_ratelimit_wait = max;
+    }
+
+    /// A copy of this handle with a different rate limit wait limit.
+    /// For a single request there's `dapi.get(&route).max_ratelimit_wait(Duration::from_secs(5)).await`
+    pub fn with_max_ratelimit_wait(&self, max: Duration) -> Self {
+        let mut this = self.clone();
+        this.max_ratelimit_wait = Some(max);
+        this
+    }
+
+    /// Rate limit events of every handle sharing this one's token.
+    pub fn subscribe_ratelimits(&self) -> broadcast::Receiver<RateLimitEvent> {
+        self.core.ratelimiter.subscribe()
     }
 
-    async fn request<R: DeserializeOwned, B: Serialize>(
+    /// A handle for another API version, sharing this one's token, connections and rate limits.
+    pub fn version<U: DApiVersion>(&self) -> DApi<U> {
+        DApi {
+            core: Arc::clone(&self.core),
+            user_agent: self.user_agent.clone(),
+            api_base: self.api_base.clone(),
+            api_ver: PhantomData,
+            retry_policy: self.retry_policy.clone(),
+            max_ratelimit_wait: self.max_ratelimit_wait,
+            timeout: self.timeout,
+        }
+    }
+
+    fn json_body<B: Serialize>(body: &B) -> Result<RequestBody> {
+        match serde_json::to_vec(body).map_err(DApiError::SerializingBody)? {
+            b if b == b"null" => Ok(RequestBody::Empty), //() bodies
+            b => Ok(RequestBody::Json(b)),
+        }
+    }
+
+    fn headers(&self, body: &HttpBody, options: &RequestOptions) -> std::result::Result<HeaderMap, Box<dyn std::error::Error + Send + Sync>> {
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", HeaderValue::from_str(self.user_agent())?);
+
+        if let Some(token) = self.core.token.as_ref().filter(|_| !options.skip_auth) {
+            headers.insert("Authorization", HeaderValue::from_str(&token.authorization())?);
+        }
+
+        if let Some(ref reason) = options.reason {
+            let reason = percent_encoding::utf8_percent_encode(reason, URL_COMPONENT).to_string();
+            headers.insert("X-Audit-Log-Reason", HeaderValue::from_str(&reason)?);
+        }
+
+        //multipart bodies bring their own content type, with the boundary in it
+        if !matches!(body, HttpBody::Multipart { .. }) {
+            headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        }
+
+        for (name, value) in &options.headers {
+            headers.insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+        }
+
+        Ok(headers)
+    }
+
+    /// Sends the request, errors come wrapped in `DApiError::Request` saying which request it was.
+    async fn request<R: DeserializeOwned>(
         &self,
         method: Method,
         path: &str,
-        body: Option<&B>,
-    ) -> Result<R> {
-        let resource = Self::path_into_resource(path);
-
-        loop {
-            {
-                let retry_at = *self.global_limit.lock().unwrap();
-                if let Some(retry_at) = retry_at {
-                    tokio::time::sleep_until(retry_at).await;
-                    *self.global_limit.lock().unwrap() = None;
+        body: Result<RequestBody>,
+        options: &RequestOptions,
+    ) -> Result<Response<R>> {
+        let route = Route::new(&method, path);
+        let mut progress = RequestProgress::default();
+        self.try_request(&method, path, &route, body, options, &mut progress)
+            .await
+            .map_err(|e| {
+                let (status, request_id) = progress.last_response.unzip();
+                DApiError::Request {
+                    context: Box::new(RequestContext {
+                        method,
+                        route: route.template().to_owned(),
+                        status,
+                        request_id: request_id.flatten(),
+                        attempts: progress.attempts,
+                    }),
+                    source: Box::new(e),
                 }
-            }
+            })
+    }
 
-            if let Some(r) = resource {
-                let lock = self.ratelimits.read().await;
-                let retry_at_lock = lock.get(r);
-                if let Some(v) = retry_at_lock {
-                    let retry_at = *v.lock().unwrap();
-                    if let Some(retry_at) = retry_at {
-                        drop(lock);
-                        tokio::time::sleep_until(retry_at).await;
-                        *self.ratelimits.read().await.get(r).unwrap().lock().unwrap() = None;
-                    }
-                } else {
-                    drop(lock);
-                    self.ratelimits
-                        .write()
-                        .await
-                        .insert(r.into(), Mutex::new(None));
+    //progress is kept up to date with the attempt count and the latest response, for the error context
+    async fn try_request<R: DeserializeOwned>(
+        &self,
+        method: &Method,
+        path: &str,
+        route: &Route,
+        body: Result<RequestBody>,
+        options: &RequestOptions,
+        progress: &mut RequestProgress,
+    ) -> Result<Response<R>> {
+        //files are read once up front, so they can be sent again on retries
+        let body = match body? {
+            RequestBody::Empty => HttpBody::Empty,
+            RequestBody::Json(b) => HttpBody::Json(b),
+            RequestBody::Multipart { payload_json, files } => {
+                let mut loaded = Vec::with_capacity(files.len());
+                for f in files {
+                    loaded.push(f.load().await?);
                 }
+                HttpBody::Multipart { payload_json, files: loaded }
             }
+        };
+        let headers = self.headers(&body, options).map_err(|e| DApiError::Requesting(TransportError::new(TransportErrorKind::Other, e)))?;
+        let url = format!("{}/{}{}", self.api_base, V::VER, path);
 
-            let mut req = self.http.request(method.clone(), self.api_base.clone() + path)
-                .header("User-Agent", &self.user_agent)
-                .header("Content-Type", "application/json");
+        let policy = &self.retry_policy;
+        let max_ratelimit_wait = options.max_ratelimit_wait.or(self.max_ratelimit_wait);
+        let mut attempt = 0;
+        let mut waited = Duration::ZERO;
 
-            if let Some(ref token) = self.token {
-                req = req.header("Authorization", token);
-            }
+        loop {
+            attempt += 1;
+            progress.attempts = attempt;
+            let ticket = self.core.ratelimiter.acquire(route, max_ratelimit_wait).await?;
 
-            if let Some(b) = body {
-                req = req.json(b);
+            let req = HttpRequest {
+                method: method.clone(),
+                url: url.clone(),
+                headers: headers.clone(),
+                body: body.clone(),
+                timeout: options.timeout.or(self.timeout),
+            };
+
+            //failures that may go away by themselves end up here, along with how long to wait before trying again
+            let resp = self.core.http.send(req).await;
+            if let Ok(ref resp) = resp {
+                progress.last_response = Some((resp.status, request_id(&resp.headers)));
             }
 
-            let resp = req.send().await.map_err(DApiError::Requesting)?;
-
-            if resp.status().as_u16() == 429 {
-                if let (Some(scope), Some(retry_after)) = (
-                    resp.headers().get("x-ratelimit-scope"),
-                    resp.headers().get("retry-after"),
-                ) {
-                    let scope = scope
-                        .to_str()
-                        .map_err(|e| DApiError::ParsingResponse(e.into()))?;
-                    let retry_after = Instant::now()
-                        + Duration::from_secs(
-                            retry_after
-                                .to_str()
-                                .map_err(|e| DApiError::ParsingResponse(e.into()))?
-                                .parse::<u64>()
-                                .map_err(|e| DApiError::ParsingResponse(e.into()))?,
-                        );
-                    if scope == "user" || scope == "global" {
-                        *self.global_limit.lock().unwrap() = Some(retry_after);
-                    } else if let Some(r) = resource {
-                        //shared
-                        *self.ratelimits.read().await.get(r).unwrap().lock().unwrap() =
-                            Some(retry_after);
-                    } else {
-                        return Err(DApiError::ParsingResponse(format!("Shared ratelimit encountered, however resource ID could not be parsed: {path}").into()));
+            let (err, delay) = match resp {
+                Err(e) if policy.should_retry_error(method, &e) => (DApiError::Requesting(e), policy.backoff(attempt)),
+                Err(e) => return Err(DApiError::Requesting(e)),
+                Ok(resp) if resp.status == StatusCode::TOO_MANY_REQUESTS => {
+                    #[derive(Deserialize)]
+                    struct RateLimitedBody {
+                        retry_after: f64,
+                        #[serde(default)]
+                        global: bool,
                     }
+
+                    let info = RateLimitInfo::from_headers(&resp.headers);
+                    let body = serde_json::from_slice::<RateLimitedBody>(&resp.body).ok();
+                    //the body has the most precise value, the Retry-After header is rounded up to whole seconds
+                    let retry_after = body.as_ref()
+                        .and_then(|b| Duration::try_from_secs_f64(b.retry_after).ok())
+                        .or(info.retry_after)
+                        .or(info.reset_after)
+                        .unwrap_or(Duration::from_secs(1));
+                    let global = info.global || body.is_some_and(|b| b.global);
+
+                    self.core.ratelimiter.complete_limited(ticket, &resp.headers, retry_after, global);
+                    let err = DApiError::RateLimited {
+                        retry_after,
+                        scope: info.scope,
+                        bucket: info.bucket,
+                        global,
+                    };
+                    if max_ratelimit_wait.is_some_and(|max| retry_after > max) {
+                        return Err(err);
+                    }
+                    //429s don't count as attempts, only max_ratelimit_wait limits them. the limiter does the waiting
+                    attempt -= 1;
+                    continue;
                 }
-            } else if resp.status().as_u16() >= 400 {
-                let err = resp.error_for_status_ref().unwrap_err();
-                if let Ok(err) = resp.json().await {
-                    return Err(DApiError::ApiError(err));
+                Ok(resp) => {
+                    self.core.ratelimiter.complete(ticket, &resp.headers);
+
+                    if policy.should_retry_status(method, resp.status) {
+                        (DApiError::ApiErrorWithoutBody(resp.status), policy.backoff(attempt))
+                    } else if resp.status.as_u16() >= 400 {
+                        if let Ok(err) = serde_json::from_slice(&resp.body) {
+                            return Err(DApiError::ApiError(err));
+                        }
+                        return Err(DApiError::ApiErrorWithoutBody(resp.status));
+                    } else {
+                        //no content responses, type R should be wrapped in an Option to produce a None value
+                        let body = if resp.status.as_u16() == 204 { &b"null"[..] } else { &resp.body };
+                        return Ok(Response {
+                            body: serde_json::from_slice(body).map_err(|e| DApiError::ParsingResponse(e.into()))?,
+                            ratelimit: RateLimitInfo::from_headers(&resp.headers),
+                            status: resp.status,
+                            headers: resp.headers,
+                        });
+                    }
                 }
-                return Err(DApiError::ApiErrorWithoutBody(err));
-            } else if resp.status().as_u16() == 204 {
-                //no content responses, type R should be wrapped in an Option to produce a None value
-                return serde_json::from_str("null")
-                    .map_err(|e| DApiError::ParsingResponse(e.into()));
-            } else {
-                //success
-                return resp
-                    .json()
-                    .await
-                    .map_err(|e| DApiError::ParsingResponse(e.into()));
+            };
+
+            if attempt >= policy.max_attempts || waited + delay > policy.max_total_wait {
+                return Err(if attempt > 1 {
+                    DApiError::RetriesExhausted { attempts: attempt, source: Box::new(err) }
+                } else {
+                    err
+                });
             }
+
+            waited += delay;
+            tokio::time::sleep(delay).await;
         }
     }
 
-    pub async fn get<T: DApiGET<V> + ?Sized>(&self, route: &T) -> Result<T::Response> {
-        self.request(Method::GET, route.path(), None as Option<&()>)
-            .await
+    pub fn get<'a, T: DApiGET<V> + ?Sized>(&'a self, route: &'a T) -> DApiRequest<'a, V, T::Response> {
+        DApiRequest::new(self, Method::GET, route.path(), Ok(RequestBody::Empty))
     }
 
-    pub async fn post<T: DApiPOST<V> + ?Sized>(
-        &self,
-        route: &T,
-        body: &T::Body,
-    ) -> Result<T::Response> {
-        self.request(Method::POST, route.path(), Some(body)).await
+    pub fn post<'a, T: DApiPOST<V> + ?Sized>(&'a self, route: &'a T, body: &T::Body) -> DApiRequest<'a, V, T::Response> {
+        DApiRequest::new(self, Method::POST, route.path(), Self::json_body(body))
     }
 
-    pub async fn put<T: DApiPUT<V> + ?Sized>(
-        &self,
-        route: &T,
-        body: &T::Body,
-    ) -> Result<T::Response> {
-        self.request(Method::PUT, route.path(), Some(body)).await
+    pub fn put<'a, T: DApiPUT<V> + ?Sized>(&'a self, route: &'a T, body: &T::Body) -> DApiRequest<'a, V, T::Response> {
+        DApiRequest::new(self, Method::PUT, route.path(), Self::json_body(body))
     }
 
-    pub async fn patch<T: DApiPATCH<V> + ?Sized>(
-        &self,
-        route: &T,
+    pub fn patch<'a, T: DApiPATCH<V> + ?Sized>(&'a self, route: &'a T, body: &T::Body) -> DApiRequest<'a, V, T::Response> {
+        DApiRequest::new(self, Method::PATCH, route.path(), Self::json_body(body))
+    }
+
+    pub fn delete<'a, T: DApiDELETE<V> + ?Sized>(&'a self, route: &'a T, body: &T::Body) -> DApiRequest<'a, V, T::Response> {
+        DApiRequest::new(self, Method::DELETE, route.path(), Self::json_body(body))
+    }
+
+    /// A request to any path (relative to the API version, like routes), for endpoints without a route:
+    /// `dapi.raw(Method::GET, "/users/@me/relationships").query("limit", 10).await?`
+    /// It's rate limited and retried like any other request.
+    pub fn raw<'a>(&'a self, method: Method, path: &str) -> RawRequest<'a, V> {
+        RawRequest::new(self, method, path)
+    }
+
+    /// Streams every item of a paginated listing:
+    /// `dapi.paginate(pagination::channel_messages(channel_id)).before(message_id).limit(500)`
+    pub fn paginate<P: Pageable<V>>(&self, listing: P) -> Paginator<'_, V, P> {
+        Paginator::new(self, listing)
+    }
+
+    /// Fetches an asset from the cdn, through this handle's transport and with its user agent and timeout.
+    pub async fn download(&self, asset: &CdnUrl) -> Result<Vec<u8>> {
+        let url = asset.url().map_err(DApiError::InvalidCdnUrl)?;
+        self.download_url(&url).await
+    }
+
+    /// Fetches any url, e.g. an attachment's. No token is sent along and the rate limits don't apply.
+    pub async fn download_url(&self, url: &str) -> Result<Vec<u8>> {
+        let mut headers = HeaderMap::new();
+        let user_agent = HeaderValue::from_str(self.user_agent())
+            .map_err(|e| DApiError::Requesting(TransportError::new(TransportErrorKind::Other, e)))?;
+        headers.insert("User-Agent", user_agent);
+
+        let resp = self.core.http.send(HttpRequest {
+            method: Method::GET,
+            url: url.to_owned(),
+            headers,
+            body: HttpBody::Empty,
+            timeout: self.timeout,
+        })
+        .await
+        .map_err(DApiError::Requesting)?;
+
+        if !resp.status.is_success() {
+            return Err(DApiError::ApiErrorWithoutBody(resp.status));
+        }
+        Ok(resp.body)
+    }
+
+    fn multipart_body<B: MultipartBody>(body: &B, files: Vec<FileUpload>) -> Result<RequestBody> {
+        Ok(RequestBody::Multipart { payload_json: multipart::payload_json(body, &files)?, files })
+    }
+
+    /// Sends the body as multipart/form-data along with `files`, which become attachments of the message.
+    pub fn post_with_files<'a, T: DApiPOST<V> + ?Sized>(
+        &'a self,
+        route: &'a T,
         body: &T::Body,
-    ) -> Result<T::Response> {
-        self.request(Method::PATCH, route.path(), Some(body)).await
+        files: Vec<FileUpload>,
+    ) -> DApiRequest<'a, V, T::Response>
+    where
+        T::Body: MultipartBody,
+    {
+        DApiRequest::new(self, Method::POST, route.path(), Self::multipart_body(body, files))
     }
 
-    pub async fn delete<T: DApiDELETE<V> + ?Sized>(
-        &self,
-        route: &T,
+    /// Like `post_with_files`. Attachments the message already has are dropped unless they're listed in the body.
+    pub fn patch_with_files<'a, T: DApiPATCH<V> + ?Sized>(
+        &'a self,
+        route: &'a T,
         body: &T::Body,
-    ) -> Result<T::Response> {
-        self.request(Method::DELETE, route.path(), Some(body)).await
+        files: Vec<FileUpload>,
+    ) -> DApiRequest<'a, V, T::Response>
+    where
+        T::Body: MultipartBody,
+    {
+        DApiRequest::new(self, Method::PATCH, route.path(), Self::multipart_body(body, files))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        mock::{MockResponse, MockTransport},
+        retry::RetryPolicyBuilder,
+        versions::v10,
+        *,
+    };
+
+    fn dapi(mock: &MockTransport, max_attempts: u32) -> DApi<v10> {
+        let mut dapi = DApi::<v10>::with_transport(mock.clone());
+        dapi.set_retry_policy(
+            RetryPolicyBuilder::default()
+                .max_attempts(max_attempts)
+                .base_delay(Duration::from_millis(1))
+                .max_delay(Duration::from_millis(1))
+                .max_total_wait(Duration::from_secs(1))
+                .build()
+                .unwrap(),
+        );
+        dapi
+    }
+
+    #[tokio::test]
+    async fn rate_limits_dont_use_up_attempts() {
+        let mock = MockTransport::new();
+        mock.push_rate_limited(3, 0.01, false).push(MockResponse::ok(serde_json::json!({ "id": "1" })));
+
+        let body = dapi(&mock, 2).raw(Method::GET, "/users/@me").send().await.unwrap();
+        assert_eq!(body["id"], "1");
+        assert_eq!(mock.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn rate_limits_over_the_max_wait_fail() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::rate_limited(5.0, false));
+        let err = dapi(&mock, 4)
+            .raw(Method::GET, "/users/@me")
+            .max_ratelimit_wait(Duration::from_millis(10))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(matches!(err.root(), DApiError::RateLimited { global: false, .. }));
+        assert_eq!(err.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried_until_max_attempts() {
+        let mock = MockTransport::new();
+        mock.fallback(MockResponse::status(503));
+
+        let err = dapi(&mock, 3).raw(Method::GET, "/users/@me").send().await.unwrap_err();
+        assert!(matches!(err, DApiError::Request { ref source, .. } if matches!(**source, DApiError::RetriesExhausted { attempts: 3, .. })));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_after_a_retry_keep_the_attempt() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::status(503)).push(MockResponse::status(404));
+
+        let err = dapi(&mock, 3).raw(Method::GET, "/users/@me").send().await.unwrap_err();
+        assert!(matches!(err.root(), DApiError::ApiErrorWithoutBody(StatusCode::NOT_FOUND)));
+        assert_eq!(err.context().unwrap().attempts, 2);
+        assert!(err.to_string().contains("on attempt 2"));
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn raw_queries_extend_the_paths_own() {
+        let mock = MockTransport::new();
+        mock.fallback(MockResponse::no_content());
+        let dapi = dapi(&mock, 1);
+
+        dapi.raw(Method::GET, "guilds/1/members?limit=5").query("after", 10).send().await.unwrap();
+        assert!(mock.last_request().unwrap().url.ends_with("/v10/guilds/1/members?limit=5&after=10"));
+
+        dapi.raw(Method::GET, "/guilds/1/members").query("limit", 5).query("after", 10).send().await.unwrap();
+        assert!(mock.last_request().unwrap().url.ends_with("/v10/guilds/1/members?limit=5&after=10"));
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_requests_arent_retried() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::status(503)).push(MockResponse::no_content());
+
+        let err = dapi(&mock, 3).raw(Method::POST, "/channels/1/messages").send().await.unwrap_err();
+        assert!(matches!(err.root(), DApiError::ApiErrorWithoutBody(StatusCode::SERVICE_UNAVAILABLE)));
+        assert_eq!(mock.requests().len(), 1);
     }
 }
@@ -0,0 +1,140 @@
+#![allow(unused)]
+//multipart/form-data uploads, see https://discord.com/developers/docs/reference#uploading-files
+
+use std::{fmt, path::PathBuf};
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::{
+    transport::MultipartFile,
+    types::{DApiError, Result},
+};
+
+enum FileSource {
+    Bytes(Vec<u8>),
+    Path(PathBuf),
+    Reader(Box<dyn AsyncRead + Send + Unpin>),
+}
+
+/// A file to upload with a request, sent as the `files[n]` part and linked to attachment `n` of the payload.
+pub struct FileUpload {
+    filename: String,
+    description: Option<String>,
+    spoiler: bool,
+    source: FileSource,
+}
+
+impl fmt::Debug for FileUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileUpload")
+            .field("filename", &self.filename)
+            .field("description", &self.description)
+            .field("spoiler", &self.spoiler)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileUpload {
+    fn new(filename: impl Into<String>, source: FileSource) -> Self {
+        Self {
+            filename: filename.into(),
+            description: None,
+            spoiler: false,
+            source,
+        }
+    }
+
+    pub fn from_bytes(filename: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self::new(filename, FileSource::Bytes(data.into()))
+    }
+
+    /// The file is read when the request is sent, named after the last component of the path.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let filename = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Self::new(filename, FileSource::Path(path))
+    }
+
+    /// The reader is read to the end when the request is sent.
+    pub fn from_reader(filename: impl Into<String>, reader: impl AsyncRead + Send + Unpin + 'static) -> Self {
+        Self::new(filename, FileSource::Reader(Box::new(reader)))
+    }
+
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = filename.into();
+        self
+    }
+
+    /// Alt text of the attachment.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn spoiler(mut self, spoiler: bool) -> Self {
+        self.spoiler = spoiler;
+        self
+    }
+
+    //discord marks spoilers by the filename
+    fn upload_filename(&self) -> String {
+        if self.spoiler && !self.filename.starts_with("SPOILER_") {
+            format!("SPOILER_{}", self.filename)
+        } else {
+            self.filename.clone()
+        }
+    }
+
+    /// Reads the file into memory, so the request can be resent if it has to be retried.
+    pub(crate) async fn load(self) -> Result<MultipartFile> {
+        let filename = self.upload_filename();
+        let data = match self.source {
+            FileSource::Bytes(b) => b,
+            FileSource::Path(p) => tokio::fs::read(&p).await.map_err(DApiError::ReadingFile)?,
+            FileSource::Reader(mut r) => {
+                let mut buf = Vec::new();
+                r.read_to_end(&mut buf).await.map_err(DApiError::ReadingFile)?;
+                buf
+            }
+        };
+
+        Ok(MultipartFile { filename, data })
+    }
+}
+
+pub type JsonObject = Map<String, Value>;
+
+/// Request bodies that can carry files. The uploads are added to the `attachments` array of the object
+/// returned by `attachments_parent`, with the ids of their `files[n]` parts.
+pub trait MultipartBody: Serialize {
+    fn attachments_parent(payload: &mut JsonObject) -> Option<&mut JsonObject> {
+        Some(payload)
+    }
+}
+
+//for raw requests
+impl MultipartBody for Value {}
+
+/// The payload_json part, with the files linked to their attachments.
+pub(crate) fn payload_json<B: MultipartBody>(body: &B, files: &[FileUpload]) -> Result<String> {
+    let mut payload = serde_json::to_value(body).map_err(DApiError::SerializingBody)?;
+
+    if let Some(parent) = payload.as_object_mut().and_then(B::attachments_parent) {
+        let attachments = parent.entry("attachments").or_insert(Value::Null);
+        if !attachments.is_array() {
+            *attachments = Value::Array(Vec::new());
+        }
+        let attachments = attachments.as_array_mut().unwrap();
+        for (i, f) in files.iter().enumerate() {
+            let mut a = json!({ "id": i, "filename": f.upload_filename() });
+            if let Some(d) = &f.description {
+                a["description"] = d.as_str().into();
+            }
+            attachments.push(a);
+        }
+    }
+
+    Ok(payload.to_string())
+}
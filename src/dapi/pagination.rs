@@ -0,0 +1,280 @@
+#![allow(unused)]
+//walking cursor-paginated listings as streams
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{future::BoxFuture, FutureExt, Stream};
+use serde::de::DeserializeOwned;
+
+use super::{
+    types::{DApiGET, DApiVersion, Result},
+    DApi,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Towards older items (lower ids), each page starts below the lowest cursor of the last one.
+    Before,
+    /// Towards newer items (higher ids), each page starts above the highest cursor of the last one.
+    After,
+}
+
+/// A listing that's fetched a page at a time, see `routes::v10::pagination` for the ones discord has.
+pub trait Pageable<V: DApiVersion> {
+    type Item;
+    type Cursor: Copy + Ord + Send;
+    type Page: DeserializeOwned;
+
+    /// The direction used when none is picked.
+    const DIRECTION: Direction;
+    const MAX_PAGE_SIZE: u32 = 100;
+
+    fn page(
+        &self,
+        direction: Direction,
+        cursor: Option<Self::Cursor>,
+        limit: u32,
+    ) -> impl DApiGET<V, Response = Self::Page> + Send + 'static;
+
+    /// The items of a page, and whether there's more after it if the listing says so.
+    fn items(page: Self::Page) -> (Vec<Self::Item>, Option<bool>);
+
+    fn cursor(item: &Self::Item) -> Option<Self::Cursor>;
+}
+
+/// Listings that can be walked towards older items.
+pub trait PageBackward<V: DApiVersion>: Pageable<V> {}
+/// Listings that can be walked towards newer items.
+pub trait PageForward<V: DApiVersion>: Pageable<V> {}
+
+type PageFuture<'a, P, V> = BoxFuture<'a, Result<<P as Pageable<V>>::Page>>;
+
+/// A stream of every item of a listing, fetching the next page once the current one runs out.
+/// Ends after a page shorter than requested, an empty one, or one saying there's nothing more.
+/// Pages go through the rate limiter like any other request.
+#[must_use = "streams do nothing unless polled"]
+pub struct Paginator<'a, V: DApiVersion, P: Pageable<V>> {
+    dapi: &'a DApi<V>,
+    listing: P,
+    direction: Direction,
+    cursor: Option<P::Cursor>,
+    page_size: u32,
+    remaining: Option<usize>,
+    buffer: VecDeque<P::Item>,
+    fetching: Option<PageFuture<'a, P, V>>,
+    done: bool,
+}
+
+impl<'a, V: DApiVersion, P: Pageable<V>> Paginator<'a, V, P> {
+    pub(crate) fn new(dapi: &'a DApi<V>, listing: P) -> Self {
+        Self {
+            dapi,
+            listing,
+            direction: P::DIRECTION,
+            cursor: None,
+            page_size: P::MAX_PAGE_SIZE,
+            remaining: None,
+            buffer: VecDeque::new(),
+            fetching: None,
+            done: false,
+        }
+    }
+
+    /// Items per request, capped at what the listing allows.
+    pub fn page_size(mut self, size: u32) -> Self {
+        self.page_size = size.clamp(1, P::MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Stops after `limit` items in total.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.remaining = Some(limit);
+        self
+    }
+}
+
+impl<'a, V: DApiVersion, P: PageBackward<V>> Paginator<'a, V, P> {
+    pub fn before(mut self, cursor: impl Into<P::Cursor>) -> Self {
+        self.direction = Direction::Before;
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+impl<'a, V: DApiVersion, P: PageForward<V>> Paginator<'a, V, P> {
+    pub fn after(mut self, cursor: impl Into<P::Cursor>) -> Self {
+        self.direction = Direction::After;
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+impl<'a, V, P> Stream for Paginator<'a, V, P>
+where
+    V: DApiVersion + Sync + 'a,
+    P: Pageable<V> + Unpin,
+    P::Item: Unpin,
+    P::Cursor: Unpin,
+    P::Page: Send + 'a,
+{
+    type Item = Result<P::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.remaining == Some(0) {
+                return Poll::Ready(None);
+            }
+            if let Some(item) = this.buffer.pop_front() {
+                if let Some(r) = this.remaining.as_mut() {
+                    *r -= 1;
+                }
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let limit = this.remaining
+                .map_or(this.page_size, |r| this.page_size.min(r.try_into().unwrap_or(u32::MAX)));
+            let fetching = this.fetching.get_or_insert_with(|| {
+                let route = this.listing.page(this.direction, this.cursor, limit);
+                let dapi = this.dapi;
+                async move { dapi.get(&route).send().await }.boxed()
+            });
+
+            let page = match fetching.poll_unpin(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(page) => page,
+            };
+            this.fetching = None;
+
+            let (items, has_more) = match page {
+                Ok(page) => P::items(page),
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            let cursors = items.iter().filter_map(P::cursor);
+            let next = match this.direction {
+                Direction::Before => cursors.min(),
+                Direction::After => cursors.max(),
+            };
+            this.done = next.is_none() || !has_more.unwrap_or(items.len() as u32 >= limit);
+            this.cursor = next.or(this.cursor);
+            this.buffer.extend(items);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::dapi::{
+        mock::{MockResponse, MockTransport},
+        retry::RetryPolicy,
+        routes::v10::pagination::{current_user_guilds, joined_private_archived_threads},
+        versions::v10,
+    };
+
+    fn dapi(mock: &MockTransport) -> DApi<v10> {
+        let mut dapi = DApi::<v10>::with_transport(mock.clone());
+        dapi.set_retry_policy(RetryPolicy::none());
+        dapi
+    }
+
+    fn guilds(ids: &[u64]) -> Value {
+        ids.iter().map(|id| json!({ "id": id.to_string(), "name": "guild", "icon": null })).collect()
+    }
+
+    fn threads(ids: &[u64], has_more: bool) -> Value {
+        let threads: Vec<_> = ids.iter().map(|id| json!({ "id": id.to_string(), "type": 12 })).collect();
+        json!({ "threads": threads, "members": [], "has_more": has_more })
+    }
+
+    fn queries(mock: &MockTransport) -> Vec<String> {
+        mock.requests().into_iter().map(|r| r.url.split_once('?').map_or("", |(_, q)| q).to_owned()).collect()
+    }
+
+    #[tokio::test]
+    async fn walks_forward_from_the_highest_id() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(guilds(&[1, 2])))
+            .push(MockResponse::ok(guilds(&[4, 3])))
+            .push(MockResponse::ok(guilds(&[5])));
+        let dapi = dapi(&mock);
+
+        let ids: Vec<u64> = dapi.paginate(current_user_guilds()).page_size(2)
+            .map(|g| g.unwrap().id.get())
+            .collect().await;
+        assert_eq!(ids, [1, 2, 4, 3, 5]);
+        assert_eq!(queries(&mock), ["limit=2", "after=2&limit=2", "after=4&limit=2"]);
+    }
+
+    #[tokio::test]
+    async fn walks_backward_from_the_lowest_id() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(guilds(&[9, 8])))
+            .push(MockResponse::ok(guilds(&[6, 7])))
+            .push(MockResponse::ok(guilds(&[])));
+        let dapi = dapi(&mock);
+
+        let ids: Vec<u64> = dapi.paginate(current_user_guilds()).before(10).page_size(2)
+            .map(|g| g.unwrap().id.get())
+            .collect().await;
+        assert_eq!(ids, [9, 8, 6, 7]);
+        assert_eq!(queries(&mock), ["before=10&limit=2", "before=8&limit=2", "before=6&limit=2"]);
+    }
+
+    #[tokio::test]
+    async fn limit_shrinks_the_last_page() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(guilds(&[1, 2, 3])))
+            .push(MockResponse::ok(guilds(&[4, 5])));
+        let dapi = dapi(&mock);
+
+        let ids: Vec<u64> = dapi.paginate(current_user_guilds()).page_size(3).limit(5)
+            .map(|g| g.unwrap().id.get())
+            .collect().await;
+        assert_eq!(ids, [1, 2, 3, 4, 5]);
+        assert_eq!(queries(&mock), ["limit=3", "after=3&limit=2"]);
+    }
+
+    #[tokio::test]
+    async fn has_more_decides_over_the_page_length() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(threads(&[30], true)))
+            .push(MockResponse::ok(threads(&[29, 28], false)));
+        let dapi = dapi(&mock);
+
+        let ids: Vec<u64> = dapi.paginate(joined_private_archived_threads(1.into())).page_size(2)
+            .map(|t| t.unwrap().id.get())
+            .collect().await;
+        assert_eq!(ids, [30, 29, 28]);
+        assert_eq!(queries(&mock), ["limit=2", "before=30&limit=2"]);
+    }
+
+    #[tokio::test]
+    async fn errors_end_the_stream() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(guilds(&[1, 2])))
+            .push(MockResponse::status(500))
+            .fallback(MockResponse::ok(guilds(&[3, 4])));
+        let dapi = dapi(&mock);
+
+        let results: Vec<_> = dapi.paginate(current_user_guilds()).page_size(2).collect().await;
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(|r| r.is_ok()));
+        assert!(results[2].is_err());
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
@@ -0,0 +1,466 @@
+#![allow(unused)]
+//bucket-aware rate limiting, see https://discord.com/developers/docs/topics/rate-limits
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::{header::HeaderMap, Method};
+use tokio::{
+    sync::{broadcast, Mutex as AsyncMutex, OwnedMutexGuard},
+    time::Instant,
+};
+
+/// Identifies the rate limit of a request before its bucket is known:
+/// the method, the path with ids replaced by placeholders, and the major parameter left in.
+/// Webhook and interaction tokens only go into `major`, so `key` is safe to log.
+/// Interaction ids do as well, every interaction would get a route of its own otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Route {
+    pub key: String,
+    pub major: String,
+}
+
+impl Route {
+    pub fn new(method: &Method, path: &str) -> Self {
+        let path = path.split('?').next().unwrap_or_default();
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut template = String::new();
+        let mut major = String::new();
+
+        let mut i = 0;
+        while i < segments.len() {
+            let seg = segments[i];
+            template.push('/');
+            match (i, i.checked_sub(1).map(|p| segments[p])) {
+                (1, Some("channels" | "guilds")) => {
+                    major = seg.to_owned();
+                    template += seg;
+                }
+                //the token is a part of the major parameter for both
+                (1, Some(resource @ ("webhooks" | "interactions"))) => {
+                    major = seg.to_owned();
+                    template += if resource == "interactions" { ":id" } else { seg };
+                    if let Some(token) = segments.get(i + 1).filter(|t| !t.bytes().all(|b| b.is_ascii_digit())) {
+                        major = format!("{major}/{token}");
+                        template += "/:token";
+                        i += 1;
+                    }
+                }
+                (_, Some("reactions")) => template += ":emoji",
+                _ if seg.bytes().all(|b| b.is_ascii_digit()) => template += ":id",
+                _ => template += seg,
+            }
+            i += 1;
+        }
+
+        Self {
+            key: format!("{method} {template}"),
+            major,
+        }
+    }
+
+    /// The path part of `key`, e.g. `/channels/123/messages/:id`.
+    pub fn template(&self) -> &str {
+        self.key.split_once(' ').map_or(&self.key, |(_, t)| t)
+    }
+}
+
+/// Rate limit headers of a response.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitInfo {
+    pub bucket: Option<String>,
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    pub reset_after: Option<Duration>,
+    pub retry_after: Option<Duration>,
+    pub global: bool,
+    pub scope: Option<String>,
+}
+
+impl RateLimitInfo {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let secs = |name: &str| get(name)
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(|s| Duration::try_from_secs_f64(s).ok());
+
+        Self {
+            bucket: get("x-ratelimit-bucket").map(Into::into),
+            limit: get("x-ratelimit-limit").and_then(|v| v.parse().ok()),
+            remaining: get("x-ratelimit-remaining").and_then(|v| v.parse().ok()),
+            reset_after: secs("x-ratelimit-reset-after"),
+            retry_after: secs("retry-after"),
+            global: get("x-ratelimit-global").is_some_and(|v| v.eq_ignore_ascii_case("true")),
+            scope: get("x-ratelimit-scope").map(Into::into),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct BucketState {
+    known: bool, //a response for this bucket has been seen
+    hash: Option<String>,
+    scope: Option<String>,
+    limit: Option<u32>,
+    remaining: u32,
+    reset_at: Option<Instant>,
+}
+
+impl BucketState {
+    /// When the bucket frees up, if it's exhausted right now.
+    fn exhausted_until(&self) -> Option<Instant> {
+        self.reset_at.filter(|r| self.remaining == 0 && *r > Instant::now())
+    }
+}
+
+#[derive(Default, Debug)]
+struct Bucket {
+    queue: Arc<AsyncMutex<()>>, //requests waiting on the bucket line up here
+    state: Mutex<BucketState>,
+}
+
+impl Bucket {
+    //nobody holds or waits on the queue and the limits have reset, a fresh bucket would behave the same
+    fn is_stale(&self) -> bool {
+        Arc::strong_count(&self.queue) == 1
+            && self.state.lock().unwrap().reset_at.is_none_or(|r| r <= Instant::now())
+    }
+}
+
+/// Permission to send a request. While its bucket is still unknown, the ticket keeps the bucket's queue locked
+/// so only one request goes out until the limits are discovered.
+pub struct RateLimitTicket {
+    route: Route,
+    bucket: Arc<Bucket>,
+    queue: Option<OwnedMutexGuard<()>>,
+}
+
+/// A request would have to wait longer than it's allowed to.
+#[derive(Clone, Debug)]
+pub struct RateLimitExceeded {
+    pub retry_after: Duration,
+    pub scope: Option<String>,
+    pub bucket: Option<String>,
+    pub global: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitEventKind {
+    /// Discord responded with a 429.
+    Hit,
+    /// A request gave up instead of waiting past its limit.
+    GaveUp,
+}
+
+#[derive(Clone, Debug)]
+pub struct RateLimitEvent {
+    pub kind: RateLimitEventKind,
+    pub route: String,
+    pub bucket: Option<String>,
+    pub scope: Option<String>,
+    pub retry_after: Duration,
+    pub global: bool,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    global: Mutex<Option<Instant>>,
+    routes: Mutex<HashMap<(String, String), Arc<Bucket>>>, //route key + major parameter -> bucket
+    buckets: Mutex<HashMap<String, Arc<Bucket>>>, //discovered bucket hash + major parameter -> bucket
+    events: broadcast::Sender<RateLimitEvent>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            global: Default::default(),
+            routes: Default::default(),
+            buckets: Default::default(),
+            events: broadcast::channel(64).0,
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rate limits hit and requests given up on from now on. Slow receivers miss the oldest events.
+    pub fn subscribe(&self) -> broadcast::Receiver<RateLimitEvent> {
+        self.events.subscribe()
+    }
+
+    fn bucket(&self, route: &Route) -> Arc<Bucket> {
+        let mut routes = self.routes.lock().unwrap();
+        let key = (route.key.clone(), route.major.clone());
+        if let Some(bucket) = routes.get(&key) {
+            return bucket.clone();
+        }
+
+        //every webhook and interaction token gets its own buckets, so they're swept out before adding more
+        routes.retain(|_, b| !b.is_stale());
+        self.buckets.lock().unwrap().retain(|_, b| !b.is_stale());
+        routes.entry(key).or_default().clone()
+    }
+
+    fn give_up(&self, route: &Route, exceeded: RateLimitExceeded) -> RateLimitExceeded {
+        let _ = self.events.send(RateLimitEvent {
+            kind: RateLimitEventKind::GaveUp,
+            route: route.key.clone(),
+            bucket: exceeded.bucket.clone(),
+            scope: exceeded.scope.clone(),
+            retry_after: exceeded.retry_after,
+            global: exceeded.global,
+        });
+        exceeded
+    }
+
+    /// Waits until a request on `route` can be sent without hitting a limit.
+    /// Fails right away if that would take longer than `max_wait`.
+    pub async fn acquire(&self, route: &Route, max_wait: Option<Duration>) -> Result<RateLimitTicket, RateLimitExceeded> {
+        let deadline = max_wait.map(|w| Instant::now() + w);
+        let past_deadline = |until: Instant| deadline.is_some_and(|d| until > d);
+
+        loop {
+            let global = *self.global.lock().unwrap();
+            match global {
+                Some(until) if until > Instant::now() => {
+                    if past_deadline(until) {
+                        return Err(self.give_up(route, RateLimitExceeded {
+                            retry_after: until - Instant::now(),
+                            scope: Some("global".into()),
+                            bucket: None,
+                            global: true,
+                        }));
+                    }
+                    tokio::time::sleep_until(until).await;
+                }
+                _ => break,
+            }
+        }
+
+        let bucket = self.bucket(route);
+        let exceeded = |bucket: &Bucket| {
+            let state = bucket.state.lock().unwrap();
+            RateLimitExceeded {
+                retry_after: state.reset_at.map(|r| r.saturating_duration_since(Instant::now())).unwrap_or_default(),
+                scope: state.scope.clone(),
+                bucket: state.hash.clone(),
+                global: false,
+            }
+        };
+
+        //no point in queueing up if the bucket won't free up in time anyway
+        let exhausted_until = bucket.state.lock().unwrap().exhausted_until();
+        if exhausted_until.is_some_and(past_deadline) {
+            return Err(self.give_up(route, exceeded(&bucket)));
+        }
+
+        //the queue is held while sleeping, so waiting requests go out in order once the bucket resets
+        let queue = match deadline {
+            Some(d) => match tokio::time::timeout_at(d, bucket.queue.clone().lock_owned()).await {
+                Ok(q) => q,
+                Err(_) => return Err(self.give_up(route, exceeded(&bucket))),
+            },
+            None => bucket.queue.clone().lock_owned().await,
+        };
+
+        let exhausted_until = bucket.state.lock().unwrap().exhausted_until();
+        if let Some(until) = exhausted_until {
+            if past_deadline(until) {
+                return Err(self.give_up(route, exceeded(&bucket)));
+            }
+            tokio::time::sleep_until(until).await;
+        }
+
+        let known = {
+            let mut state = bucket.state.lock().unwrap();
+            if state.reset_at.is_some_and(|r| r <= Instant::now()) {
+                state.remaining = state.limit.unwrap_or(1);
+                state.reset_at = None;
+            }
+            state.remaining = state.remaining.saturating_sub(1);
+            state.known
+        };
+
+        Ok(RateLimitTicket {
+            route: route.clone(),
+            bucket,
+            queue: (!known).then_some(queue),
+        })
+    }
+
+    /// Records the limits a response came with and releases the ticket.
+    pub fn complete(&self, ticket: RateLimitTicket, headers: &HeaderMap) {
+        self.record(ticket, &RateLimitInfo::from_headers(headers), None);
+    }
+
+    /// Records a 429, requests on the route (or all of them if `global`) wait `retry_after` before going out again.
+    pub fn complete_limited(&self, ticket: RateLimitTicket, headers: &HeaderMap, retry_after: Duration, global: bool) {
+        let info = RateLimitInfo::from_headers(headers);
+        let _ = self.events.send(RateLimitEvent {
+            kind: RateLimitEventKind::Hit,
+            route: ticket.route.key.clone(),
+            bucket: info.bucket.clone(),
+            scope: info.scope.clone(),
+            retry_after,
+            global,
+        });
+
+        if global {
+            let until = Instant::now() + retry_after;
+            {
+                let mut lock = self.global.lock().unwrap();
+                if lock.is_none_or(|u| u < until) {
+                    *lock = Some(until);
+                }
+            }
+            self.record(ticket, &info, None);
+        } else {
+            self.record(ticket, &info, Some(retry_after));
+        }
+    }
+
+    fn record(&self, ticket: RateLimitTicket, info: &RateLimitInfo, limited_for: Option<Duration>) {
+        let RateLimitTicket { route, bucket, queue } = ticket;
+        let mut state = bucket.state.lock().unwrap();
+
+        state.known = true;
+        if info.bucket.is_some() {
+            state.hash = info.bucket.clone();
+        }
+        if info.scope.is_some() {
+            state.scope = info.scope.clone();
+        }
+        if let Some(limit) = info.limit {
+            state.limit = Some(limit);
+        }
+        if let Some(remaining) = info.remaining {
+            state.remaining = remaining;
+        }
+        if let Some(reset_after) = info.reset_after {
+            state.reset_at = Some(Instant::now() + reset_after);
+        }
+        if let Some(retry_after) = limited_for {
+            let until = Instant::now() + retry_after;
+            state.remaining = 0;
+            state.reset_at = Some(state.reset_at.map_or(until, |r| r.max(until)));
+        }
+        drop(state);
+        drop(queue);
+
+        //routes sharing a bucket hash (within the same major parameter) share their limits from now on
+        if let Some(hash) = &info.bucket {
+            let canonical = self.buckets.lock().unwrap()
+                .entry(format!("{hash}:{}", route.major))
+                .or_insert_with(|| bucket.clone())
+                .clone();
+            if !Arc::ptr_eq(&canonical, &bucket) {
+                self.routes.lock().unwrap().insert((route.key, route.major), canonical);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn ids_are_replaced_but_majors_kept() {
+        let route = Route::new(&Method::GET, "/channels/123/messages/456");
+        assert_eq!(route.key, "GET /channels/123/messages/:id");
+        assert_eq!(route.major, "123");
+
+        let route = Route::new(&Method::GET, "/guilds/1/members?limit=5&after=10");
+        assert_eq!(route.key, "GET /guilds/1/members");
+        assert_eq!(route.major, "1");
+
+        let route = Route::new(&Method::DELETE, "/users/@me/guilds/5");
+        assert_eq!(route.key, "DELETE /users/@me/guilds/:id");
+        assert_eq!(route.major, "");
+    }
+
+    #[test]
+    fn methods_and_majors_make_separate_routes() {
+        let get = Route::new(&Method::GET, "/channels/1/messages/2");
+        assert_ne!(get, Route::new(&Method::DELETE, "/channels/1/messages/2"));
+        assert_ne!(get, Route::new(&Method::GET, "/channels/3/messages/2"));
+        assert_eq!(get, Route::new(&Method::GET, "/channels/1/messages/4"));
+    }
+
+    #[test]
+    fn emojis_share_a_route() {
+        let route = Route::new(&Method::PUT, "/channels/1/messages/2/reactions/%F0%9F%91%8D/@me");
+        assert_eq!(route.key, "PUT /channels/1/messages/:id/reactions/:emoji/@me");
+        assert_eq!(route, Route::new(&Method::PUT, "/channels/1/messages/3/reactions/name:123/@me"));
+    }
+
+    #[test]
+    fn tokens_stay_out_of_the_key() {
+        let route = Route::new(&Method::PATCH, "/webhooks/9/s3cr3t-token/messages/5");
+        assert_eq!(route.key, "PATCH /webhooks/9/:token/messages/:id");
+        assert_eq!(route.major, "9/s3cr3t-token");
+        assert_eq!(route.template(), "/webhooks/9/:token/messages/:id");
+
+        let route = Route::new(&Method::POST, "/interactions/7/s3cr3t-token/callback");
+        assert_eq!(route.key, "POST /interactions/:id/:token/callback");
+        assert_eq!(route.major, "7/s3cr3t-token");
+
+        //without a token
+        let route = Route::new(&Method::GET, "/webhooks/9");
+        assert_eq!(route.key, "GET /webhooks/9");
+        assert_eq!(route.major, "9");
+    }
+
+    #[tokio::test]
+    async fn exhausted_buckets_fail_past_the_max_wait() {
+        let limiter = RateLimiter::new();
+        let route = Route::new(&Method::POST, "/channels/1/messages");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-bucket", HeaderValue::from_static("abc"));
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-after", HeaderValue::from_static("30"));
+        let ticket = limiter.acquire(&route, None).await.unwrap();
+        limiter.complete(ticket, &headers);
+
+        let err = limiter.acquire(&route, Some(Duration::from_millis(10))).await.err().expect("the bucket is exhausted");
+        assert_eq!(err.bucket.as_deref(), Some("abc"));
+        assert!(err.retry_after > Duration::from_secs(29));
+
+        //other channels have their own bucket
+        let other = Route::new(&Method::POST, "/channels/2/messages");
+        assert!(limiter.acquire(&other, Some(Duration::from_millis(10))).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn stale_buckets_are_swept_out() {
+        let limiter = RateLimiter::new();
+        let mut exhausted = HeaderMap::new();
+        exhausted.insert("x-ratelimit-bucket", HeaderValue::from_static("abc"));
+        exhausted.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        exhausted.insert("x-ratelimit-reset-after", HeaderValue::from_static("30"));
+        let limited = Route::new(&Method::POST, "/channels/1/messages");
+        let ticket = limiter.acquire(&limited, None).await.unwrap();
+        limiter.complete(ticket, &exhausted);
+
+        for i in 0..20 {
+            let route = Route::new(&Method::POST, &format!("/interactions/{i}/token-{i}/callback"));
+            let ticket = limiter.acquire(&route, None).await.unwrap();
+            limiter.complete(ticket, &HeaderMap::new());
+        }
+
+        //the last interaction's bucket and the exhausted one
+        assert_eq!(limiter.routes.lock().unwrap().len(), 2);
+        assert!(limiter.routes.lock().unwrap().contains_key(&(limited.key, limited.major)));
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+#![allow(unused)]
+//requests being put together, they go out when awaited
+
+use std::{
+    borrow::Cow,
+    future::{Future, IntoFuture},
+    marker::PhantomData,
+    pin::Pin,
+    time::Duration,
+};
+
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    multipart::{self, FileUpload, MultipartBody},
+    ratelimit::RateLimitInfo,
+    types::{with_query, DApiVersion, Result},
+    DApi,
+};
+
+pub(crate) enum RequestBody {
+    Empty,
+    Json(Vec<u8>),
+    Multipart { payload_json: String, files: Vec<FileUpload> },
+}
+
+/// Everything about a request besides its route and body.
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    /// Shows up in the guild's audit log for the actions the request takes.
+    pub reason: Option<String>,
+    /// Overrides the handle's timeout.
+    pub timeout: Option<Duration>,
+    /// Overrides the handle's max rate limit wait, see `DApi::set_max_ratelimit_wait`.
+    pub max_ratelimit_wait: Option<Duration>,
+    pub headers: Vec<(String, String)>,
+    /// Leaves out the Authorization header, for routes authorized by a token in the path.
+    pub skip_auth: bool,
+}
+
+/// A successful response along with what came with it, see `DApiRequest::with_response`.
+#[derive(Clone, Debug)]
+pub struct Response<T> {
+    pub body: T,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub ratelimit: RateLimitInfo,
+}
+
+/// A request to the API, sent by awaiting it (or `send()`) after setting any options:
+/// `dapi.delete(&route, &()).reason("spam").timeout(Duration::from_secs(10)).await`
+#[must_use = "requests do nothing unless awaited"]
+pub struct DApiRequest<'a, V: DApiVersion, R> {
+    dapi: &'a DApi<V>,
+    method: Method,
+    path: Cow<'a, str>,
+    body: Result<RequestBody>,
+    options: RequestOptions,
+    response: PhantomData<fn() -> R>,
+}
+
+impl<'a, V: DApiVersion, R: DeserializeOwned> DApiRequest<'a, V, R> {
+    pub(crate) fn new(dapi: &'a DApi<V>, method: Method, path: impl Into<Cow<'a, str>>, body: Result<RequestBody>) -> Self {
+        Self {
+            dapi,
+            method,
+            path: path.into(),
+            body,
+            options: Default::default(),
+            response: PhantomData,
+        }
+    }
+
+    /// Sent as the URL-encoded X-Audit-Log-Reason header.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.options.reason = Some(reason.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails with `DApiError::RateLimited` instead of waiting longer than `max` for a rate limit.
+    pub fn max_ratelimit_wait(mut self, max: Duration) -> Self {
+        self.options.max_ratelimit_wait = Some(max);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn without_auth(mut self) -> Self {
+        self.options.skip_auth = true;
+        self
+    }
+
+    /// Replaces every option set so far.
+    pub fn options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub async fn send(self) -> Result<R> {
+        self.send_with_response().await.map(|r| r.body)
+    }
+
+    /// Resolves to the whole `Response` instead of just the body once awaited.
+    pub fn with_response(self) -> WithResponse<'a, V, R> {
+        WithResponse(self)
+    }
+
+    pub async fn send_with_response(self) -> Result<Response<R>> {
+        self.dapi.request(self.method, &self.path, self.body, &self.options).await
+    }
+}
+
+impl<'a, V, R> IntoFuture for DApiRequest<'a, V, R>
+where
+    V: DApiVersion + Sync + 'a,
+    R: DeserializeOwned + Send + 'a,
+{
+    type Output = Result<R>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<R>> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+#[must_use = "requests do nothing unless awaited"]
+pub struct WithResponse<'a, V: DApiVersion, R>(DApiRequest<'a, V, R>);
+
+impl<'a, V, R> IntoFuture for WithResponse<'a, V, R>
+where
+    V: DApiVersion + Sync + 'a,
+    R: DeserializeOwned + Send + 'a,
+{
+    type Output = Result<Response<R>>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<Response<R>>> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.0.send_with_response())
+    }
+}
+
+/// A request to any endpoint, for trying out the ones without a route, see `DApi::raw`.
+/// Resolves to the response as a `serde_json::Value` once awaited, `.returning::<T>()` picks another type.
+#[must_use = "requests do nothing unless awaited"]
+pub struct RawRequest<'a, V: DApiVersion> {
+    dapi: &'a DApi<V>,
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Result<RequestBody>,
+    options: RequestOptions,
+}
+
+impl<'a, V: DApiVersion> RawRequest<'a, V> {
+    pub(crate) fn new(dapi: &'a DApi<V>, method: Method, path: &str) -> Self {
+        Self {
+            dapi,
+            method,
+            path: format!("/{}", path.trim_start_matches('/')),
+            query: vec![],
+            body: Ok(RequestBody::Empty),
+            options: Default::default(),
+        }
+    }
+
+    pub fn json(mut self, body: &impl Serialize) -> Self {
+        self.body = DApi::<V>::json_body(body);
+        self
+    }
+
+    /// A multipart/form-data body, `files` are added to the `attachments` array of `body` like in `DApi::post_with_files`.
+    pub fn multipart(mut self, body: &impl MultipartBody, files: Vec<FileUpload>) -> Self {
+        self.body = multipart::payload_json(body, &files).map(|payload_json| RequestBody::Multipart { payload_json, files });
+        self
+    }
+
+    /// Appends a query parameter, in the order they're added.
+    pub fn query(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.options.reason = Some(reason.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails with `DApiError::RateLimited` instead of waiting longer than `max` for a rate limit.
+    pub fn max_ratelimit_wait(mut self, max: Duration) -> Self {
+        self.options.max_ratelimit_wait = Some(max);
+        self
+    }
+
+    pub fn without_auth(mut self) -> Self {
+        self.options.skip_auth = true;
+        self
+    }
+
+    /// The same request, deserializing the response into `T`.
+    pub fn returning<T: DeserializeOwned>(self) -> DApiRequest<'a, V, T> {
+        let path = if self.query.is_empty() {
+            self.path
+        } else {
+            with_query(&self.path, &self.query)
+        };
+        DApiRequest::new(self.dapi, self.method, path, self.body).options(self.options)
+    }
+
+    pub async fn send(self) -> Result<serde_json::Value> {
+        self.returning().send().await
+    }
+}
+
+impl<'a, V> IntoFuture for RawRequest<'a, V>
+where
+    V: DApiVersion + Sync + 'a,
+{
+    type Output = Result<serde_json::Value>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value>> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.returning().into_future()
+    }
+}
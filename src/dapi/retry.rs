@@ -0,0 +1,86 @@
+#![allow(unused)]
+//retrying requests that failed for reasons that may go away by themselves
+
+use std::time::Duration;
+
+use derive_builder::Builder;
+use rand::Rng;
+use reqwest::{Method, StatusCode};
+
+use super::transport::{TransportError, TransportErrorKind};
+
+/// When and how often failed requests are sent again.
+/// Requests that never reached discord (connection errors) and 429s are always safe to retry,
+/// timeouts and 502/503/504 only for idempotent methods unless `retry_non_idempotent` is set,
+/// as the request might have gone through already.
+#[derive(Clone, Debug, Builder)]
+#[builder(default, setter(into))]
+pub struct RetryPolicy {
+    /// Including the first one, 1 disables retrying.
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled with every following one.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Total time spent in backoffs after which the request fails. 429s count against neither this nor `max_attempts`,
+    /// they're only limited by the max rate limit wait of the handle or the request.
+    pub max_total_wait: Duration,
+    /// Randomizes the backoffs, so clients failing at the same time don't retry at the same time.
+    pub jitter: bool,
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            max_total_wait: Duration::from_secs(30),
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_idempotent(method: &Method) -> bool {
+        matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE)
+    }
+
+    fn may_resend(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || Self::is_idempotent(method)
+    }
+
+    pub fn should_retry_error(&self, method: &Method, e: &TransportError) -> bool {
+        match e.kind {
+            TransportErrorKind::Connect => true,
+            TransportErrorKind::Timeout | TransportErrorKind::Request => self.may_resend(method),
+            TransportErrorKind::Body | TransportErrorKind::Other => false,
+        }
+    }
+
+    pub fn should_retry_status(&self, method: &Method, status: StatusCode) -> bool {
+        matches!(status, StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT)
+            && self.may_resend(method)
+    }
+
+    /// How long to wait after failed attempt number `attempt` (starting at 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        if self.jitter {
+            //equal jitter, keeps at least half of the delay
+            delay / 2 + delay.mul_f64(rand::thread_rng().gen_range(0.0..0.5))
+        } else {
+            delay
+        }
+    }
+}
@@ -1,7 +1,200 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    str::FromStr,
+};
+
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String;
 
-pub type Snowflake<T = String> = T;
+pub use super::error_codes::{FieldError, JsonErrorCode};
+use super::error_codes::flatten_errors;
+
+/// Zero-sized tags telling apart ids of different resources, see the aliases below.
+pub mod markers {
+    pub enum Generic {}
+    pub enum Guild {}
+    pub enum Channel {}
+    pub enum Message {}
+    pub enum User {}
+    pub enum Role {}
+    pub enum Emoji {}
+    pub enum Sticker {}
+    pub enum Attachment {}
+    pub enum Webhook {}
+    pub enum Application {}
+    pub enum Interaction {}
+    pub enum Command {}
+    pub enum Tag {}
+}
+
+/// A discord id. Ordered numerically, which is also chronologically.
+/// Serialized as a string, deserialized from either a string or an integer.
+pub struct Snowflake<M = markers::Generic>(u64, PhantomData<fn() -> M>);
+
+pub type GuildId = Snowflake<markers::Guild>;
+pub type ChannelId = Snowflake<markers::Channel>;
+pub type MessageId = Snowflake<markers::Message>;
+pub type UserId = Snowflake<markers::User>;
+pub type RoleId = Snowflake<markers::Role>;
+pub type EmojiId = Snowflake<markers::Emoji>;
+pub type StickerId = Snowflake<markers::Sticker>;
+pub type AttachmentId = Snowflake<markers::Attachment>;
+pub type WebhookId = Snowflake<markers::Webhook>;
+pub type ApplicationId = Snowflake<markers::Application>;
+pub type InteractionId = Snowflake<markers::Interaction>;
+pub type CommandId = Snowflake<markers::Command>;
+pub type TagId = Snowflake<markers::Tag>;
+
+impl<M> Snowflake<M> {
+    /// Milliseconds since the unix epoch at 2015-01-01T00:00:00Z
+    pub const DISCORD_EPOCH: u64 = 1420070400000;
+
+    pub const fn new(id: u64) -> Self {
+        Self(id, PhantomData)
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// The lowest id that could have been created at `ts`, useful as a `before`/`after` pagination cursor.
+    /// Times before the discord epoch give 0.
+    pub fn from_timestamp(ts: iso8601_timestamp::Timestamp) -> Self {
+        let ms = (ts.duration_since(iso8601_timestamp::Timestamp::UNIX_EPOCH).whole_milliseconds().max(0) as u64)
+            .saturating_sub(Self::DISCORD_EPOCH);
+        Self::new(ms << 22)
+    }
+
+    pub const fn timestamp_ms(self) -> u64 {
+        (self.0 >> 22) + Self::DISCORD_EPOCH
+    }
+
+    pub fn timestamp(self) -> iso8601_timestamp::Timestamp {
+        iso8601_timestamp::Timestamp::UNIX_EPOCH
+            + iso8601_timestamp::Duration::milliseconds(self.timestamp_ms() as i64)
+    }
+
+    pub const fn worker_id(self) -> u8 {
+        ((self.0 & 0x3E0000) >> 17) as u8
+    }
+
+    pub const fn process_id(self) -> u8 {
+        ((self.0 & 0x1F000) >> 12) as u8
+    }
+
+    pub const fn increment(self) -> u16 {
+        (self.0 & 0xFFF) as u16
+    }
+
+    /// Reinterprets the id as one of another resource, ex. a guild id as the id of its @everyone role.
+    pub const fn cast<N>(self) -> Snowflake<N> {
+        Snowflake::new(self.0)
+    }
+}
+
+//manual impls, derives would put bounds on M
+impl<M> Clone for Snowflake<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for Snowflake<M> {}
+
+impl<M> PartialEq for Snowflake<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<M> Eq for Snowflake<M> {}
+
+impl<M> PartialOrd for Snowflake<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M> Ord for Snowflake<M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<M> Hash for Snowflake<M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<M> fmt::Debug for Snowflake<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl<M> fmt::Display for Snowflake<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<M> FromStr for Snowflake<M> {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::new)
+    }
+}
+
+impl<M> From<u64> for Snowflake<M> {
+    fn from(id: u64) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<M> From<Snowflake<M>> for u64 {
+    fn from(id: Snowflake<M>) -> Self {
+        id.0
+    }
+}
+
+impl<M> Serialize for Snowflake<M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de, M> Deserialize<'de> for Snowflake<M> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SnowflakeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SnowflakeVisitor {
+            type Value = u64;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a snowflake as a string or an integer")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<u64, E> {
+                Ok(v)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<u64, E> {
+                u64::try_from(v).map_err(E::custom)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<u64, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(SnowflakeVisitor).map(Self::new)
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(untagged)]
@@ -12,7 +205,61 @@ pub enum IntOrStr {
 
 #[derive(Serialize, Deserialize, Clone, Debug, display_json::DisplayAsJsonPretty)]
 pub struct DiscordApiError {
-    pub code: u32,
+    pub code: JsonErrorCode,
     pub message: String,
     pub errors: Option<serde_json::Value>,
 }
+
+impl DiscordApiError {
+    /// The validation failures in `errors`, one per field and check.
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        self.errors.as_ref().map(flatten_errors).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iso8601_timestamp::Timestamp;
+
+    use super::*;
+
+    //the example id of the discord docs
+    const ID: Snowflake = Snowflake::new(175928847299117063);
+
+    #[test]
+    fn parts_are_extracted() {
+        assert_eq!(ID.timestamp_ms(), 1462015105796);
+        assert_eq!(ID.worker_id(), 1);
+        assert_eq!(ID.process_id(), 0);
+        assert_eq!(ID.increment(), 7);
+        assert_eq!(ID.timestamp(), Timestamp::parse("2016-04-30T11:18:25.796Z").unwrap());
+    }
+
+    #[test]
+    fn timestamps_round_trip() {
+        let id = Snowflake::<markers::Message>::from_timestamp(ID.timestamp());
+        assert_eq!(id.timestamp_ms(), ID.timestamp_ms());
+        assert_eq!((id.worker_id(), id.process_id(), id.increment()), (0, 0, 0));
+        assert!(id <= ID.cast());
+
+        assert_eq!(Snowflake::<markers::Generic>::from_timestamp(Timestamp::UNIX_EPOCH).get(), 0);
+        assert_eq!(Snowflake::<markers::Generic>::from_timestamp(Timestamp::parse("1969-07-20T20:17:40Z").unwrap()).get(), 0);
+    }
+
+    #[test]
+    fn ordered_numerically() {
+        let mut ids: Vec<UserId> = ["30", "4", "1000"].iter().map(|s| s.parse().unwrap()).collect();
+        ids.sort();
+        assert_eq!(ids, [4, 30, 1000].map(UserId::new));
+        assert!(UserId::new(u64::MAX) > UserId::new(1 << 63));
+    }
+
+    #[test]
+    fn deserialized_from_strings_and_integers() {
+        assert_eq!(serde_json::from_str::<GuildId>(r#""175928847299117063""#).unwrap(), ID.cast());
+        assert_eq!(serde_json::from_str::<GuildId>("175928847299117063").unwrap(), ID.cast());
+        assert!(serde_json::from_str::<GuildId>("-1").is_err());
+        assert!(serde_json::from_str::<GuildId>(r#""-1""#).is_err());
+        assert_eq!(serde_json::to_string(&ID).unwrap(), r#""175928847299117063""#);
+    }
+}
@@ -0,0 +1,349 @@
+#![allow(unused)]
+//the codes discord puts in json error bodies, https://discord.com/developers/docs/topics/opcodes-and-status-codes#json
+
+use serde::{Deserialize, Serialize};
+use smartstring::alias::String;
+
+macro_rules! json_error_codes {
+    ($($(#[$meta:meta])* $name:ident = $code:literal,)*) => {
+        #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[serde(from = "u32", into = "u32")]
+        pub enum JsonErrorCode {
+            $($(#[$meta])* $name,)*
+            /// A code this list doesn't know about.
+            Unknown(u32),
+        }
+
+        impl From<u32> for JsonErrorCode {
+            fn from(code: u32) -> Self {
+                match code {
+                    $($code => Self::$name,)*
+                    other => Self::Unknown(other),
+                }
+            }
+        }
+
+        impl From<JsonErrorCode> for u32 {
+            fn from(code: JsonErrorCode) -> Self {
+                match code {
+                    $(JsonErrorCode::$name => $code,)*
+                    JsonErrorCode::Unknown(other) => other,
+                }
+            }
+        }
+    };
+}
+
+json_error_codes! {
+    GeneralError = 0,
+    UnknownAccount = 10001,
+    UnknownApplication = 10002,
+    UnknownChannel = 10003,
+    UnknownGuild = 10004,
+    UnknownIntegration = 10005,
+    UnknownInvite = 10006,
+    UnknownMember = 10007,
+    UnknownMessage = 10008,
+    UnknownPermissionOverwrite = 10009,
+    UnknownProvider = 10010,
+    UnknownRole = 10011,
+    UnknownToken = 10012,
+    UnknownUser = 10013,
+    UnknownEmoji = 10014,
+    UnknownWebhook = 10015,
+    UnknownWebhookService = 10016,
+    UnknownSession = 10020,
+    UnknownAsset = 10021,
+    UnknownBan = 10026,
+    UnknownSku = 10027,
+    UnknownStoreListing = 10028,
+    UnknownEntitlement = 10029,
+    UnknownBuild = 10030,
+    UnknownLobby = 10031,
+    UnknownBranch = 10032,
+    UnknownStoreDirectoryLayout = 10033,
+    UnknownRedistributable = 10036,
+    UnknownGiftCode = 10038,
+    UnknownStream = 10049,
+    UnknownPremiumServerSubscribeCooldown = 10050,
+    UnknownGuildTemplate = 10057,
+    UnknownDiscoverableServerCategory = 10059,
+    UnknownSticker = 10060,
+    UnknownStickerPack = 10061,
+    UnknownInteraction = 10062,
+    UnknownApplicationCommand = 10063,
+    UnknownVoiceState = 10065,
+    UnknownApplicationCommandPermissions = 10066,
+    UnknownStageInstance = 10067,
+    UnknownGuildMemberVerificationForm = 10068,
+    UnknownGuildWelcomeScreen = 10069,
+    UnknownGuildScheduledEvent = 10070,
+    UnknownGuildScheduledEventUser = 10071,
+    UnknownTag = 10087,
+    BotsCannotUseEndpoint = 20001,
+    OnlyBotsCanUseEndpoint = 20002,
+    ExplicitContentCannotBeSent = 20009,
+    NotAuthorizedForApplication = 20012,
+    SlowmodeRateLimit = 20016,
+    OnlyAccountOwner = 20018,
+    AnnouncementEditRateLimit = 20022,
+    UnderMinimumAge = 20024,
+    ChannelWriteRateLimit = 20028,
+    ServerWriteRateLimit = 20029,
+    DisallowedWords = 20031,
+    GuildPremiumTierTooLow = 20035,
+    MaxGuilds = 30001,
+    MaxFriends = 30002,
+    MaxPins = 30003,
+    MaxRecipients = 30004,
+    MaxGuildRoles = 30005,
+    MaxWebhooks = 30007,
+    MaxEmojis = 30008,
+    MaxReactions = 30010,
+    MaxGroupDms = 30011,
+    MaxGuildChannels = 30013,
+    MaxAttachments = 30015,
+    MaxInvites = 30016,
+    MaxAnimatedEmojis = 30018,
+    MaxServerMembers = 30019,
+    MaxServerCategories = 30030,
+    GuildAlreadyHasTemplate = 30031,
+    MaxApplicationCommands = 30032,
+    MaxThreadParticipants = 30033,
+    MaxDailyApplicationCommandCreates = 30034,
+    MaxNonMemberBans = 30035,
+    MaxBanFetches = 30037,
+    MaxUncompletedScheduledEvents = 30038,
+    MaxStickers = 30039,
+    MaxPruneRequests = 30040,
+    MaxWidgetSettingsUpdates = 30042,
+    MaxOldMessageEdits = 30046,
+    MaxPinnedForumThreads = 30047,
+    MaxForumTags = 30048,
+    BitrateTooHigh = 30052,
+    MaxPremiumEmojis = 30056,
+    MaxGuildWebhooks = 30058,
+    MaxChannelPermissionOverwrites = 30061,
+    GuildChannelsTooLarge = 30062,
+    Unauthorized = 40001,
+    AccountVerificationRequired = 40002,
+    OpeningDmsTooFast = 40003,
+    SendMessagesTemporarilyDisabled = 40004,
+    RequestEntityTooLarge = 40005,
+    FeatureTemporarilyDisabled = 40006,
+    UserBannedFromGuild = 40007,
+    ConnectionRevoked = 40012,
+    OnlyConsumableSkus = 40018,
+    OnlySandboxEntitlementsDeletable = 40019,
+    TargetNotInVoice = 40032,
+    AlreadyCrossposted = 40033,
+    ApplicationCommandNameExists = 40041,
+    InteractionFailedToSend = 40043,
+    CannotSendInForumChannel = 40058,
+    InteractionAlreadyAcknowledged = 40060,
+    TagNamesMustBeUnique = 40061,
+    ServiceResourceRateLimited = 40062,
+    NoTagsForNonModerators = 40066,
+    TagRequired = 40067,
+    EntitlementAlreadyGranted = 40074,
+    MaxFollowUpMessages = 40094,
+    CloudflareBlocked = 40333,
+    MissingAccess = 50001,
+    InvalidAccountType = 50002,
+    CannotExecuteOnDm = 50003,
+    GuildWidgetDisabled = 50004,
+    CannotEditOtherUsersMessage = 50005,
+    CannotSendEmptyMessage = 50006,
+    CannotMessageUser = 50007,
+    CannotSendInNonTextChannel = 50008,
+    ChannelVerificationTooHigh = 50009,
+    OAuth2ApplicationWithoutBot = 50010,
+    OAuth2ApplicationLimit = 50011,
+    InvalidOAuth2State = 50012,
+    MissingPermissions = 50013,
+    InvalidAuthenticationToken = 50014,
+    NoteTooLong = 50015,
+    InvalidBulkDeleteCount = 50016,
+    InvalidMfaLevel = 50017,
+    PinInWrongChannel = 50019,
+    InvalidInviteCode = 50020,
+    CannotExecuteOnSystemMessage = 50021,
+    CannotExecuteOnChannelType = 50024,
+    InvalidOAuth2AccessToken = 50025,
+    MissingOAuth2Scope = 50026,
+    InvalidWebhookToken = 50027,
+    InvalidRole = 50028,
+    InvalidRecipients = 50033,
+    MessageTooOldToBulkDelete = 50034,
+    InvalidFormBody = 50035,
+    InviteAcceptedWithoutBot = 50036,
+    InvalidActivityAction = 50039,
+    InvalidApiVersion = 50041,
+    FileTooLarge = 50045,
+    InvalidFile = 50046,
+    /// Undocumented, sent when redeeming a gift that's already been claimed.
+    GiftAlreadyRedeemed = 50050,
+    CannotSelfRedeemGift = 50054,
+    InvalidGuild = 50055,
+    InvalidSku = 50057,
+    InvalidRequestOrigin = 50067,
+    InvalidMessageType = 50068,
+    PaymentSourceRequired = 50070,
+    CannotModifySystemWebhook = 50073,
+    CannotDeleteCommunityChannel = 50074,
+    CannotEditMessageStickers = 50080,
+    InvalidSticker = 50081,
+    ThreadArchived = 50083,
+    InvalidThreadNotificationSettings = 50084,
+    BeforeEarlierThanThreadCreation = 50085,
+    CommunityChannelsMustBeText = 50086,
+    EventEntityTypeMismatch = 50091,
+    ServerUnavailableInLocation = 50095,
+    MonetizationRequired = 50097,
+    MoreBoostsRequired = 50101,
+    InvalidJson = 50109,
+    OwnerCannotBePending = 50131,
+    CannotTransferOwnershipToBot = 50132,
+    AssetResizeFailed = 50138,
+    CannotMixSubscriptionRoles = 50144,
+    CannotConvertPremiumEmoji = 50145,
+    UploadedFileNotFound = 50146,
+    InvalidEmoji = 50151,
+    VoiceMessageWithContent = 50159,
+    VoiceMessageSingleAttachment = 50160,
+    VoiceMessageMissingMetadata = 50161,
+    VoiceMessageCannotBeEdited = 50162,
+    CannotDeleteSubscriptionIntegration = 50163,
+    VoiceMessagesNotAllowed = 50173,
+    UserMustBeVerified = 50178,
+    InvalidFileDuration = 50192,
+    NoStickerPermission = 50600,
+    TwoFactorRequired = 60003,
+    NoUsersWithDiscordTag = 80004,
+    ReactionBlocked = 90001,
+    BurstReactionsUnavailable = 90002,
+    ApplicationNotAvailable = 110001,
+    ResourceOverloaded = 130000,
+    StageAlreadyOpen = 150006,
+    ReplyWithoutReadHistory = 160002,
+    ThreadAlreadyCreated = 160004,
+    ThreadLocked = 160005,
+    MaxActiveThreads = 160006,
+    MaxActiveAnnouncementThreads = 160007,
+    InvalidLottieJson = 170001,
+    LottieWithRasterizedImages = 170002,
+    StickerFramerateExceeded = 170003,
+    StickerFrameCountExceeded = 170004,
+    LottieDimensionsExceeded = 170005,
+    StickerFramerateInvalid = 170006,
+    StickerDurationExceeded = 170007,
+    CannotUpdateFinishedEvent = 180000,
+    StageEventCreationFailed = 180002,
+    MessageBlockedByAutomod = 200000,
+    TitleBlockedByAutomod = 200001,
+    ForumWebhookNeedsThread = 220001,
+    ForumWebhookThreadNameAndId = 220002,
+    WebhookThreadsOnlyInForums = 220003,
+    WebhookServicesNotInForums = 220004,
+    MessageBlockedByHarmfulLinks = 240000,
+    FailedToBanUsers = 500000,
+    PollVotingBlocked = 520000,
+    PollExpired = 520001,
+    InvalidPollChannelType = 520002,
+    CannotEditPollMessage = 520003,
+    CannotUsePollEmoji = 520004,
+    CannotExpireNonPoll = 520006,
+}
+
+/// One failed check of a request body, from the `errors` tree of an error response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Where in the body, e.g. `embeds.0.fields.1.name`. Empty for errors about the body as a whole.
+    pub path: String,
+    /// e.g. `BASE_TYPE_REQUIRED`
+    pub code: String,
+    pub message: String,
+}
+
+//the tree mirrors the body, the errors of each field sit in its _errors array
+pub(crate) fn flatten_errors(tree: &serde_json::Value) -> Vec<FieldError> {
+    fn walk(node: &serde_json::Value, path: &mut Vec<std::string::String>, out: &mut Vec<FieldError>) {
+        let Some(obj) = node.as_object() else { return };
+        for (key, value) in obj {
+            if key == "_errors" {
+                for e in value.as_array().into_iter().flatten() {
+                    out.push(FieldError {
+                        path: path.join(".").into(),
+                        code: e["code"].as_str().unwrap_or_default().into(),
+                        message: e["message"].as_str().unwrap_or_default().into(),
+                    });
+                }
+            } else {
+                path.push(key.clone());
+                walk(value, path, out);
+                path.pop();
+            }
+        }
+    }
+
+    let mut out = vec![];
+    walk(tree, &mut vec![], &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn field_error(path: &str, code: &str, message: &str) -> FieldError {
+        FieldError { path: path.into(), code: code.into(), message: message.into() }
+    }
+
+    #[test]
+    fn errors_are_flattened_with_their_paths() {
+        let tree = json!({
+            "_errors": [{ "code": "TOO_MANY_FIELDS", "message": "Too many fields." }],
+            "content": { "_errors": [{ "code": "BASE_TYPE_MAX_LENGTH", "message": "Must be 2000 or fewer in length." }] },
+            "embeds": {
+                "0": {
+                    "fields": {
+                        "1": {
+                            "name": {
+                                "_errors": [
+                                    { "code": "BASE_TYPE_REQUIRED", "message": "This field is required" },
+                                    { "code": "BASE_TYPE_BAD_LENGTH", "message": "Must be between 1 and 256 in length." },
+                                ]
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let mut errors = flatten_errors(&tree);
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(errors, vec![
+            field_error("", "TOO_MANY_FIELDS", "Too many fields."),
+            field_error("content", "BASE_TYPE_MAX_LENGTH", "Must be 2000 or fewer in length."),
+            field_error("embeds.0.fields.1.name", "BASE_TYPE_REQUIRED", "This field is required"),
+            field_error("embeds.0.fields.1.name", "BASE_TYPE_BAD_LENGTH", "Must be between 1 and 256 in length."),
+        ]);
+    }
+
+    #[test]
+    fn malformed_trees_give_nothing() {
+        assert!(flatten_errors(&json!(null)).is_empty());
+        assert!(flatten_errors(&json!({ "content": "not a tree" })).is_empty());
+        assert!(flatten_errors(&json!({ "_errors": "not a list" })).is_empty());
+    }
+
+    #[test]
+    fn unknown_codes_round_trip() {
+        assert_eq!(JsonErrorCode::from(50035), JsonErrorCode::InvalidFormBody);
+        assert_eq!(serde_json::from_value::<JsonErrorCode>(json!(99999)).unwrap(), JsonErrorCode::Unknown(99999));
+        assert_eq!(serde_json::to_value(JsonErrorCode::Unknown(99999)).unwrap(), json!(99999));
+        assert_eq!(serde_json::to_value(JsonErrorCode::UnknownGiftCode).unwrap(), json!(10038));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod common_types;
+pub mod error_codes;
 pub mod v10;
 pub mod v6;
@@ -0,0 +1,213 @@
+#![allow(unused)]
+//keeping registered application commands in line with the ones declared in code
+
+use std::collections::HashMap;
+
+use smartstring::alias::String;
+
+use crate::dapi::{types::Result, versions::v10, DApi};
+
+use super::{
+    super::common_types::{ApplicationId, GuildId},
+    application_command, application_commands, application_commands_get, application_guild_command,
+    application_guild_commands, application_guild_commands_get,
+    types::{ApplicationCommand, ApplicationCommandPayload, ApplicationCommandType},
+};
+
+/// What a sync had to change. Commands are matched by type and name.
+#[derive(Clone, Debug, Default)]
+pub struct CommandSyncReport {
+    pub created: Vec<ApplicationCommand>,
+    pub updated: Vec<ApplicationCommand>,
+    pub deleted: Vec<ApplicationCommand>,
+    pub unchanged: usize,
+}
+
+impl CommandSyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+//edits leave out whatever isn't sent, so the defaults `matches` assumes are spelled out to reset them
+fn update_payload(cmd: &ApplicationCommandPayload) -> ApplicationCommandPayload {
+    ApplicationCommandPayload {
+        name_localizations: Some(cmd.name_localizations.clone().unwrap_or_default()),
+        description_localizations: Some(cmd.description_localizations.clone().unwrap_or_default()),
+        dm_permission: Some(cmd.dm_permission.unwrap_or(true)),
+        nsfw: Some(cmd.nsfw.unwrap_or(false)),
+        ..cmd.clone()
+    }
+}
+
+/// Makes the application's global commands (or a guild's, if `guild_id` is set) match `local`,
+/// creating, editing and deleting only the commands that differ. Running it again with the same
+/// commands changes nothing.
+pub async fn sync_commands(
+    dapi: &DApi<v10>,
+    application_id: ApplicationId,
+    guild_id: Option<GuildId>,
+    local: &[ApplicationCommandPayload],
+) -> Result<CommandSyncReport> {
+    let registered = match guild_id {
+        Some(g) => dapi.get(&application_guild_commands_get(application_id, g, true)).await?,
+        None => dapi.get(&application_commands_get(application_id, true)).await?,
+    };
+
+    let mut registered: HashMap<(ApplicationCommandType, String), ApplicationCommand> = registered.into_iter()
+        .map(|c| ((c.r#type.unwrap_or(ApplicationCommandType::CHAT_INPUT), c.name.clone()), c))
+        .collect();
+    let mut report = CommandSyncReport::default();
+
+    for cmd in local {
+        match registered.remove(&(cmd.command_type(), cmd.name.clone())) {
+            Some(existing) if cmd.matches(&existing) => report.unchanged += 1,
+            Some(existing) => {
+                let cmd = &update_payload(cmd);
+                let updated = match guild_id {
+                    Some(g) => dapi.patch(&application_guild_command(application_id, g, existing.id), cmd).await?,
+                    None => dapi.patch(&application_command(application_id, existing.id), cmd).await?,
+                };
+                report.updated.push(updated);
+            }
+            None => {
+                let created = match guild_id {
+                    Some(g) => dapi.post(&application_guild_commands(application_id, g), cmd).await?,
+                    None => dapi.post(&application_commands(application_id), cmd).await?,
+                };
+                report.created.push(created);
+            }
+        }
+    }
+
+    //whatever's left isn't declared anymore
+    for (_, stale) in registered {
+        match guild_id {
+            Some(g) => dapi.delete(&application_guild_command(application_id, g, stale.id), &()).await?,
+            None => dapi.delete(&application_command(application_id, stale.id), &()).await?,
+        }
+        report.deleted.push(stale);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Method;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::dapi::{
+        mock::{MockResponse, MockTransport},
+        retry::RetryPolicy,
+    };
+
+    fn registered(id: u64, name: &str, description: &str, options: Value) -> Value {
+        json!({
+            "id": id.to_string(),
+            "type": 1,
+            "application_id": "10",
+            "guild_id": null,
+            "name": name,
+            "name_localizations": {},
+            "description": description,
+            "description_localizations": null,
+            "options": options,
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "nsfw": false,
+            "version": "1",
+        })
+    }
+
+    fn local(name: &str, description: &str, options: Value) -> ApplicationCommandPayload {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": description,
+            "options": options,
+            "default_member_permissions": null,
+        }))
+        .unwrap()
+    }
+
+    fn dapi(mock: &MockTransport) -> DApi<v10> {
+        let mut dapi = DApi::<v10>::with_transport(mock.clone());
+        dapi.set_retry_policy(RetryPolicy::none());
+        dapi
+    }
+
+    #[tokio::test]
+    async fn only_differing_commands_are_sent() {
+        let mock = MockTransport::new();
+        let echo_option = json!([{ "type": 3, "name": "text", "description": "what to say" }]);
+        mock.push(MockResponse::ok(json!([
+            //discord fills in the defaults of options
+            registered(1, "echo", "Repeats you", json!([{ "type": 3, "name": "text", "description": "what to say", "required": false }])),
+            registered(2, "ping", "Pong", Value::Null),
+            registered(3, "old", "Not declared anymore", Value::Null),
+        ])))
+        .push(MockResponse::ok(registered(2, "ping", "Pong!", Value::Null)))
+        .push(MockResponse::ok(registered(4, "hello", "Says hi", Value::Null)))
+        .push(MockResponse::no_content());
+
+        let commands = [local("echo", "Repeats you", echo_option), local("ping", "Pong!", json!([])), local("hello", "Says hi", json!([]))];
+        let report = sync_commands(&dapi(&mock), ApplicationId::new(10), None, &commands).await.unwrap();
+
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.updated.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["ping"]);
+        assert_eq!(report.created.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["hello"]);
+        assert_eq!(report.deleted.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["old"]);
+
+        let requests: Vec<_> = mock.requests().into_iter().map(|r| (r.method, r.url)).collect();
+        assert_eq!(requests, [
+            (Method::GET, "https://discord.com/api/v10/applications/10/commands?with_localizations=true".to_owned()),
+            (Method::PATCH, "https://discord.com/api/v10/applications/10/commands/2".to_owned()),
+            (Method::POST, "https://discord.com/api/v10/applications/10/commands".to_owned()),
+            (Method::DELETE, "https://discord.com/api/v10/applications/10/commands/3".to_owned()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn synced_commands_are_left_alone() {
+        let mock = MockTransport::new();
+        mock.push(MockResponse::ok(json!([registered(2, "ping", "Pong", Value::Null)])));
+
+        let report = sync_commands(&dapi(&mock), ApplicationId::new(10), Some(GuildId::new(5)), &[local("ping", "Pong", json!([]))])
+            .await
+            .unwrap();
+        assert!(report.is_empty());
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(mock.requests().len(), 1);
+        assert!(mock.last_request().unwrap().url.ends_with("/applications/10/guilds/5/commands?with_localizations=true"));
+    }
+
+    #[tokio::test]
+    async fn removed_options_and_flags_are_reset() {
+        let mock = MockTransport::new();
+        let mut with_extras = registered(1, "echo", "Repeats you", json!([{ "type": 3, "name": "text", "description": "what to say" }]));
+        with_extras["nsfw"] = json!(true);
+        with_extras["name_localizations"] = json!({ "pl": "powtorz" });
+        let settled = registered(1, "echo", "Repeats you", Value::Null);
+        mock.push(MockResponse::ok(json!([with_extras])))
+            .push(MockResponse::ok(settled.clone()))
+            .push(MockResponse::ok(json!([settled])));
+
+        let dapi = dapi(&mock);
+        let commands = [local("echo", "Repeats you", json!([]))];
+        let report = sync_commands(&dapi, ApplicationId::new(10), None, &commands).await.unwrap();
+        assert_eq!(report.updated.len(), 1);
+
+        let patch = mock.last_request().unwrap();
+        assert_eq!(patch.method, Method::PATCH);
+        let body = patch.body.json().unwrap();
+        assert_eq!(body["options"], json!([]));
+        assert_eq!(body["name_localizations"], json!({}));
+        assert_eq!(body["nsfw"], json!(false));
+        assert_eq!(body["dm_permission"], json!(true));
+
+        let report = sync_commands(&dapi, ApplicationId::new(10), None, &commands).await.unwrap();
+        assert!(report.is_empty());
+        assert_eq!(report.unchanged, 1);
+    }
+}
@@ -0,0 +1,437 @@
+#![allow(unused)]
+//message components, see https://discord.com/developers/docs/interactions/message-components
+
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use smartstring::alias::String;
+
+use super::types::{ChannelType, Emoji};
+
+pub const MAX_ACTION_ROWS: usize = 5;
+pub const MAX_ROW_COMPONENTS: usize = 5;
+pub const MAX_CUSTOM_ID_LEN: usize = 100;
+pub const MAX_SELECT_OPTIONS: usize = 25;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentType(u8);
+impl ComponentType {
+    pub const ACTION_ROW: Self = Self(1);
+    pub const BUTTON: Self = Self(2);
+    pub const STRING_SELECT: Self = Self(3);
+    pub const TEXT_INPUT: Self = Self(4);
+    pub const USER_SELECT: Self = Self(5);
+    pub const ROLE_SELECT: Self = Self(6);
+    pub const MENTIONABLE_SELECT: Self = Self(7);
+    pub const CHANNEL_SELECT: Self = Self(8);
+
+    pub fn is_select_menu(self) -> bool {
+        matches!(self.0, 3 | 5..=8)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Component {
+    ActionRow(ActionRow),
+    Button(Box<Button>),
+    SelectMenu(Box<SelectMenu>),
+    TextInput(Box<TextInput>),
+    /// A type this file doesn't model yet, kept as it came so it can be sent back unchanged.
+    Unknown {
+        r#type: ComponentType,
+        raw: serde_json::Value,
+    },
+}
+
+impl Component {
+    pub fn r#type(&self) -> ComponentType {
+        match self {
+            Self::ActionRow(_) => ComponentType::ACTION_ROW,
+            Self::Button(_) => ComponentType::BUTTON,
+            Self::SelectMenu(s) => s.r#type,
+            Self::TextInput(_) => ComponentType::TEXT_INPUT,
+            Self::Unknown { r#type, .. } => *r#type,
+        }
+    }
+
+    pub fn custom_id(&self) -> Option<&str> {
+        match self {
+            Self::ActionRow(_) => None,
+            Self::Button(b) => b.custom_id.as_deref(),
+            Self::SelectMenu(s) => Some(&s.custom_id),
+            Self::TextInput(t) => Some(&t.custom_id),
+            Self::Unknown { raw, .. } => raw.get("custom_id").and_then(|id| id.as_str()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Tagged<'a, T> {
+    r#type: ComponentType,
+    #[serde(flatten)]
+    inner: &'a T,
+}
+
+impl Serialize for Component {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let r#type = self.r#type();
+        match self {
+            Self::ActionRow(row) => row.serialize(serializer), //these carry their own type
+            Self::SelectMenu(s) => s.serialize(serializer),
+            Self::Unknown { raw, .. } => raw.serialize(serializer),
+            Self::Button(inner) => Tagged { r#type, inner: &**inner }.serialize(serializer),
+            Self::TextInput(inner) => Tagged { r#type, inner: &**inner }.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Component { //integer tags, so no #[serde(tag = "type")]. RawValue can't be used either, messages get deserialized through #[serde(flatten)]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let r#type = value.get("type")
+            .ok_or(serde::de::Error::missing_field("type"))
+            .and_then(|t| ComponentType::deserialize(t).map_err(serde::de::Error::custom))?;
+
+        macro_rules! inner {
+            () => { serde_json::from_value(value).map_err(serde::de::Error::custom)? };
+        }
+
+        Ok(match r#type {
+            ComponentType::ACTION_ROW => Self::ActionRow(inner!()),
+            ComponentType::BUTTON => Self::Button(inner!()),
+            ComponentType::TEXT_INPUT => Self::TextInput(inner!()),
+            t if t.is_select_menu() => Self::SelectMenu(inner!()),
+            r#type => Self::Unknown { r#type, raw: value },
+        })
+    }
+}
+
+impl From<ActionRow> for Component {
+    fn from(c: ActionRow) -> Self {
+        Self::ActionRow(c)
+    }
+}
+
+impl From<Button> for Component {
+    fn from(c: Button) -> Self {
+        Self::Button(Box::new(c))
+    }
+}
+
+impl From<SelectMenu> for Component {
+    fn from(c: SelectMenu) -> Self {
+        Self::SelectMenu(Box::new(c))
+    }
+}
+
+impl From<TextInput> for Component {
+    fn from(c: TextInput) -> Self {
+        Self::TextInput(Box::new(c))
+    }
+}
+
+/// Top-level container, messages and modals can only have action rows as their components.
+/// Serialized with its `type`, also when it's not wrapped in a `Component`.
+#[derive(Deserialize, Clone, Builder, Debug, Default)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct ActionRow {
+    #[builder(default, setter(each(name = "component", into)))]
+    pub components: Vec<Component>,
+}
+
+impl Serialize for ActionRow {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Untagged<'a> {
+            components: &'a Vec<Component>,
+        }
+
+        let inner = &Untagged { components: &self.components };
+        Tagged { r#type: ComponentType::ACTION_ROW, inner }.serialize(serializer)
+    }
+}
+
+impl ActionRowBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        validate_row(self.components.as_deref().unwrap_or_default())
+    }
+}
+
+impl ActionRow {
+    /// Value of a text input from a modal submission.
+    pub fn text_input_value(&self, custom_id: &str) -> Option<&str> {
+        self.components.iter().find_map(|c| match c {
+            Component::TextInput(t) if t.custom_id == custom_id => t.value.as_deref(),
+            _ => None,
+        })
+    }
+}
+
+/// Checks the row limits: up to 5 buttons, or a single select menu or text input, and no nested rows.
+pub fn validate_row(components: &[Component]) -> Result<(), std::string::String> {
+    if components.is_empty() {
+        return Err("An action row needs at least one component".into());
+    }
+    if components.len() > MAX_ROW_COMPONENTS {
+        return Err(format!("An action row can hold at most {MAX_ROW_COMPONENTS} components"));
+    }
+    for c in components {
+        match c {
+            Component::ActionRow(_) => return Err("Action rows can't be nested".into()),
+            Component::SelectMenu(_) | Component::TextInput(_) if components.len() > 1 => {
+                return Err("Select menus and text inputs have to be the only component in their row".into())
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Checks the top-level components of a message: up to 5 action rows, each valid on its own.
+pub fn validate_components(components: &[Component]) -> Result<(), std::string::String> {
+    if components.len() > MAX_ACTION_ROWS {
+        return Err(format!("A message can have at most {MAX_ACTION_ROWS} action rows"));
+    }
+    for c in components {
+        let Component::ActionRow(row) = c else {
+            return Err("Top-level components have to be action rows".into());
+        };
+        validate_row(&row.components)?;
+    }
+    Ok(())
+}
+
+fn validate_custom_id(custom_id: Option<&str>) -> Result<(), std::string::String> {
+    match custom_id {
+        Some(id) if id.chars().count() > MAX_CUSTOM_ID_LEN => {
+            Err(format!("custom_id can be at most {MAX_CUSTOM_ID_LEN} characters long"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonStyle(u8);
+impl ButtonStyle {
+    pub const PRIMARY: Self = Self(1);
+    pub const SECONDARY: Self = Self(2);
+    pub const SUCCESS: Self = Self(3);
+    pub const DANGER: Self = Self(4);
+    pub const LINK: Self = Self(5); //needs url instead of custom_id
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into), build_fn(validate = "Self::validate"))]
+pub struct Button {
+    pub style: ButtonStyle,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[builder(default)]
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl ButtonBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        let custom_id = self.custom_id.as_ref().and_then(|c| c.as_deref());
+        let has_url = matches!(self.url, Some(Some(_)));
+        match self.style {
+            Some(ButtonStyle::LINK) if !has_url || custom_id.is_some() => {
+                Err("Link buttons need a url and can't have a custom_id".into())
+            }
+            Some(s) if s != ButtonStyle::LINK && (custom_id.is_none() || has_url) => {
+                Err("Non-link buttons need a custom_id and can't have a url".into())
+            }
+            _ => validate_custom_id(custom_id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct SelectOption {
+    pub label: String,
+    pub value: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[builder(default)]
+    #[serde(default)]
+    pub default: bool,
+}
+
+/// Any of the select menu kinds, told apart by `type`. Only STRING_SELECT takes `options`,
+/// only CHANNEL_SELECT takes `channel_types`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into), build_fn(validate = "Self::validate"))]
+pub struct SelectMenu {
+    pub r#type: ComponentType,
+    pub custom_id: String,
+    #[builder(default, setter(each(name = "option", into)))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<SelectOption>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_types: Option<Vec<ChannelType>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_values: Option<u8>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values: Option<u8>,
+    #[builder(default)]
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl SelectMenuBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        let options = self.options.as_deref().unwrap_or_default();
+        match self.r#type {
+            Some(t) if !t.is_select_menu() => return Err("Not a select menu type".into()),
+            Some(ComponentType::STRING_SELECT) if options.is_empty() => {
+                return Err("String select menus need at least one option".into())
+            }
+            Some(t) if t != ComponentType::STRING_SELECT && !options.is_empty() => {
+                return Err("Only string select menus take options".into())
+            }
+            _ => (),
+        }
+        if options.len() > MAX_SELECT_OPTIONS {
+            return Err(format!("A select menu can have at most {MAX_SELECT_OPTIONS} options"));
+        }
+        let (min, max) = (self.min_values.flatten(), self.max_values.flatten());
+        if min.is_some_and(|m| m as usize > MAX_SELECT_OPTIONS) || max.is_some_and(|m| m == 0 || m as usize > MAX_SELECT_OPTIONS) {
+            return Err(format!("min_values has to be within 0-{0} and max_values within 1-{0}", MAX_SELECT_OPTIONS));
+        }
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err("min_values can't be greater than max_values".into());
+            }
+        }
+        validate_custom_id(self.custom_id.as_deref())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextInputStyle(u8);
+impl TextInputStyle {
+    pub const SHORT: Self = Self(1);
+    pub const PARAGRAPH: Self = Self(2);
+}
+
+impl Default for TextInputStyle {
+    fn default() -> Self {
+        Self::SHORT
+    }
+}
+
+/// Modals only. Submitted text inputs come back with just `custom_id` and `value`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into), build_fn(validate = "Self::validate"))]
+pub struct TextInput {
+    pub custom_id: String,
+    #[serde(default)]
+    pub style: TextInputStyle,
+    #[serde(default)]
+    pub label: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u16>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u16>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+impl TextInputBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        if self.label.as_ref().is_some_and(|l| l.chars().count() > 45) {
+            return Err("A text input's label can be at most 45 characters long".into());
+        }
+        let (min, max) = (self.min_length.flatten(), self.max_length.flatten());
+        if min.is_some_and(|m| m > 4000) || max.is_some_and(|m| m == 0 || m > 4000) {
+            return Err("min_length has to be within 0-4000 and max_length within 1-4000".into());
+        }
+        validate_custom_id(self.custom_id.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn known_components_round_trip() {
+        let raw = json!([{
+            "type": 1,
+            "components": [
+                { "type": 2, "style": 1, "label": "ok", "custom_id": "ok", "disabled": false },
+                { "type": 2, "style": 5, "url": "https://example.com", "disabled": true },
+            ],
+        }]);
+        let components: Vec<Component> = serde_json::from_value(raw.clone()).unwrap();
+        let Component::ActionRow(row) = &components[0] else { panic!("not an action row") };
+        assert_eq!(row.components[0].custom_id(), Some("ok"));
+        assert_eq!(serde_json::to_value(&components).unwrap(), raw);
+    }
+
+    #[test]
+    fn unknown_components_round_trip() {
+        let raw = json!({
+            "type": 1,
+            "components": [{ "type": 17, "accent_color": 5, "components": [{ "type": 10, "content": "hi" }] }],
+        });
+        let component: Component = serde_json::from_value(raw.clone()).unwrap();
+        let Component::ActionRow(row) = &component else { panic!("not an action row") };
+        assert!(matches!(row.components[0], Component::Unknown { r#type: ComponentType(17), .. }));
+        assert_eq!(serde_json::to_value(&component).unwrap(), raw);
+    }
+
+    #[test]
+    fn bare_action_rows_are_tagged() {
+        let row = ActionRowBuilder::default()
+            .component(TextInputBuilder::default().custom_id("name").style(TextInputStyle::SHORT).label("Name").build().unwrap())
+            .build()
+            .unwrap();
+        let value = serde_json::to_value(&row).unwrap();
+        assert_eq!(value["type"], 1);
+        assert_eq!(value["components"][0]["type"], 4);
+        assert_eq!(serde_json::to_value(Component::from(row)).unwrap(), value);
+    }
+
+    #[test]
+    fn rows_are_validated() {
+        let button = || Component::from(ButtonBuilder::default().style(ButtonStyle::PRIMARY).custom_id("b").build().unwrap());
+        assert!(validate_row(&vec![button(); 5]).is_ok());
+        assert!(validate_row(&vec![button(); 6]).is_err());
+        assert!(validate_row(&[]).is_err());
+        assert!(validate_components(&[button()]).is_err());
+        assert!(ButtonBuilder::default().style(ButtonStyle::LINK).custom_id("b").build().is_err());
+    }
+}
@@ -1,46 +1,439 @@
 #![allow(unused)]
 
+use iso8601_timestamp::Timestamp;
+
 use crate::dapi::{
-    routes::v10::types::{Message, MessagePayload, User},
-    types::{dapi_endpoint, DApiDELETE, DApiGET, DApiPOST, DApiVersion},
+    routes::v10::types::{
+        ActiveThreads, ApplicationCommand, ApplicationCommandPayload, ApplicationCommandPermissionsPayload,
+        ApplicationCommandsQuery, ArchivedThreads, ArchivedThreadsQuery, Ban, BanPayload, BulkBanPayload,
+        BulkBanResponse, BulkDeletePayload, Channel, ChannelCreatePayload, ChannelEditPayload, ChannelPositionPayload,
+        CurrentUserGuildsQuery, ForumThread, GetChannelMessagesQuery, Guild, GuildApplicationCommandPermissions,
+        GuildBansQuery, GuildEditPayload, GuildMember, GuildMemberEditPayload, GuildMembersQuery,
+        GuildMembersSearchQuery, GuildPreview, GuildQuery, InteractionResponse, JoinedArchivedThreadsQuery, Message,
+        MessageEditPayload, MessagePayload, PermissionOverwritePayload, PrunePayload, PruneQuery, PruneResult,
+        ReactionsQuery, Role, RolePayload, RolePositionPayload, StartForumThreadPayload, StartThreadFromMessagePayload,
+        StartThreadPayload, ThreadMember, ThreadMembersQuery, User, UserGuild, Webhook, WebhookCreatePayload,
+        WebhookEditPayload, WebhookExecuteQuery, WebhookMessageQuery,
+    },
+    types::{dapi_endpoint, segment, DApiDELETE, DApiGET, DApiPATCH, DApiPOST, DApiPUT, DApiVersion},
     versions::v10,
 };
 
-use super::common_types::Snowflake;
+use super::common_types::{
+    ApplicationId, ChannelId, CommandId, GuildId, InteractionId, MessageId, RoleId, Snowflake, UserId, WebhookId,
+};
 
+pub mod commands;
+pub mod components;
+pub mod pagination;
+pub mod permissions;
 pub mod types;
 
+//channels
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Channel),
+    DApiPATCH = (Channel, ChannelEditPayload),
+    DApiDELETE = (Channel, ());
+
+    pub fn channel(channel_id: ChannelId) {
+        format!("/channels/{}", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), PermissionOverwritePayload),
+    DApiDELETE = ((), ());
+
+    pub fn channel_permission(channel_id: ChannelId, overwrite_id: Snowflake) {
+        format!("/channels/{}/permissions/{}", channel_id, overwrite_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = ((), ());
+
+    pub fn channel_typing(channel_id: ChannelId) {
+        format!("/channels/{}/typing", channel_id)
+    }
+}
+
+//messages
+
 dapi_endpoint! {
     version = v10,
     DApiPOST = (Message, MessagePayload);
 
-    pub fn channel_messages(channel_id: Snowflake<impl AsRef<str>>) {
-        format!("/channels/{}/messages", channel_id.as_ref())
+    pub fn channel_messages(channel_id: ChannelId) {
+        format!("/channels/{}/messages", channel_id)
     }
 }
 
-pub enum GetChannelMessagesAnchorParam<T: AsRef<str>> {
-    Around(Snowflake<T>),
-    Before(Snowflake<T>),
-    After(Snowflake<T>),
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Message>);
+
+    pub fn channel_messages_get(channel_id: ChannelId, query: GetChannelMessagesQuery) {
+        format!("/channels/{}/messages", channel_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Message),
+    DApiPATCH = (Message, MessageEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn channel_message(channel_id: ChannelId, message_id: MessageId) {
+        format!("/channels/{}/messages/{}", channel_id, message_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = ((), BulkDeletePayload);
+
+    pub fn channel_messages_bulk_delete(channel_id: ChannelId) {
+        format!("/channels/{}/messages/bulk-delete", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (Message, ());
+
+    pub fn channel_message_crosspost(channel_id: ChannelId, message_id: MessageId) {
+        format!("/channels/{}/messages/{}/crosspost", channel_id, message_id)
+    }
 }
 
 dapi_endpoint! {
     version = v10,
     DApiGET = (Vec<Message>);
 
-    pub fn channel_messages_get(channel_id: Snowflake<impl AsRef<str>>, limit: u32, anchor: Option<GetChannelMessagesAnchorParam<impl AsRef<str>>>) {
-        let p = format!("/channels/{}/messages?limit={}", channel_id.as_ref(), limit);
-        if let Some(a) = anchor {
-            p + match a {
-                GetChannelMessagesAnchorParam::Around(s) => format!("&around={}", s.as_ref()),
-                GetChannelMessagesAnchorParam::Before(s) => format!("&before={}", s.as_ref()),
-                GetChannelMessagesAnchorParam::After(s) => format!("&after={}", s.as_ref())
-            }.as_str()
-        } else { p }
+    pub fn channel_pins(channel_id: ChannelId) {
+        format!("/channels/{}/pins", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), ()),
+    DApiDELETE = ((), ());
+
+    pub fn channel_pin(channel_id: ChannelId, message_id: MessageId) {
+        format!("/channels/{}/pins/{}", channel_id, message_id)
+    }
+}
+
+//reactions, these take unicode emojis as they are and custom ones as name:id
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), ()),
+    DApiDELETE = ((), ());
+
+    pub fn channel_message_reaction_me(channel_id: ChannelId, message_id: MessageId, emoji: impl AsRef<str>) {
+        format!("/channels/{}/messages/{}/reactions/{}/@me", channel_id, message_id, segment(emoji.as_ref()))
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiDELETE = ((), ());
+
+    pub fn channel_message_reaction_user(channel_id: ChannelId, message_id: MessageId, emoji: impl AsRef<str>, user_id: UserId) {
+        format!("/channels/{}/messages/{}/reactions/{}/{}", channel_id, message_id, segment(emoji.as_ref()), user_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<User>);
+
+    pub fn channel_message_reactions_get(channel_id: ChannelId, message_id: MessageId, emoji: impl AsRef<str>, query: ReactionsQuery) {
+        format!("/channels/{}/messages/{}/reactions/{}", channel_id, message_id, segment(emoji.as_ref()))
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiDELETE = ((), ());
+
+    pub fn channel_message_reactions(channel_id: ChannelId, message_id: MessageId) {
+        format!("/channels/{}/messages/{}/reactions", channel_id, message_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiDELETE = ((), ());
+
+    pub fn channel_message_reactions_emoji(channel_id: ChannelId, message_id: MessageId, emoji: impl AsRef<str>) {
+        format!("/channels/{}/messages/{}/reactions/{}", channel_id, message_id, segment(emoji.as_ref()))
+    }
+}
+
+//threads
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (Channel, StartThreadFromMessagePayload);
+
+    pub fn channel_message_threads(channel_id: ChannelId, message_id: MessageId) {
+        format!("/channels/{}/messages/{}/threads", channel_id, message_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (Channel, StartThreadPayload);
+
+    pub fn channel_threads(channel_id: ChannelId) {
+        format!("/channels/{}/threads", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (ForumThread, StartForumThreadPayload);
+
+    pub fn channel_forum_threads(channel_id: ChannelId) {
+        format!("/channels/{}/threads", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), ()),
+    DApiDELETE = ((), ());
+
+    pub fn channel_thread_members_me(channel_id: ChannelId) {
+        format!("/channels/{}/thread-members/@me", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), ()),
+    DApiDELETE = ((), ());
+
+    pub fn channel_thread_member(channel_id: ChannelId, user_id: UserId) {
+        format!("/channels/{}/thread-members/{}", channel_id, user_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ThreadMember);
+
+    pub fn channel_thread_member_get(channel_id: ChannelId, user_id: UserId, with_member: bool) {
+        format!("/channels/{}/thread-members/{}", channel_id, user_id)
+    } ? ThreadMembersQuery { with_member: Some(with_member), ..Default::default() }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<ThreadMember>);
+
+    pub fn channel_thread_members_get(channel_id: ChannelId, query: ThreadMembersQuery) {
+        format!("/channels/{}/thread-members", channel_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ActiveThreads);
+
+    pub fn guild_threads_active(guild_id: GuildId) {
+        format!("/guilds/{}/threads/active", guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ArchivedThreads);
+
+    pub fn channel_threads_archived_public(channel_id: ChannelId, query: ArchivedThreadsQuery) {
+        format!("/channels/{}/threads/archived/public", channel_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ArchivedThreads);
+
+    pub fn channel_threads_archived_private(channel_id: ChannelId, query: ArchivedThreadsQuery) {
+        format!("/channels/{}/threads/archived/private", channel_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ArchivedThreads);
+
+    pub fn channel_users_me_threads_archived_private(channel_id: ChannelId, query: JoinedArchivedThreadsQuery) {
+        format!("/channels/{}/users/@me/threads/archived/private", channel_id)
+    } ? query
+}
+
+//guilds
+
+dapi_endpoint! {
+    version = v10,
+    DApiPATCH = (Guild, GuildEditPayload);
+
+    pub fn guild(guild_id: GuildId) {
+        format!("/guilds/{}", guild_id)
+    }
+}
+
+//with_counts fills in the approximate member and presence counts
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Guild);
+
+    pub fn guild_get(guild_id: GuildId, with_counts: bool) {
+        format!("/guilds/{}", guild_id)
+    } ? GuildQuery { with_counts: Some(with_counts) }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (GuildPreview);
+
+    pub fn guild_preview(guild_id: GuildId) {
+        format!("/guilds/{}/preview", guild_id)
+    }
+}
+
+//PATCH reorders the channels
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Channel>),
+    DApiPOST = (Channel, ChannelCreatePayload),
+    DApiPATCH = ((), Vec<ChannelPositionPayload>);
+
+    pub fn guild_channels(guild_id: GuildId) {
+        format!("/guilds/{}/channels", guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<GuildMember>);
+
+    pub fn guild_members_get(guild_id: GuildId, query: GuildMembersQuery) {
+        format!("/guilds/{}/members", guild_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<GuildMember>);
+
+    pub fn guild_members_search(guild_id: GuildId, query: GuildMembersSearchQuery) {
+        format!("/guilds/{}/members/search", guild_id)
+    } ? query
+}
+
+//DELETE kicks the member
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (GuildMember),
+    DApiPATCH = (GuildMember, GuildMemberEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn guild_member(guild_id: GuildId, user_id: UserId) {
+        format!("/guilds/{}/members/{}", guild_id, user_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPUT = ((), ()),
+    DApiDELETE = ((), ());
+
+    pub fn guild_member_role(guild_id: GuildId, user_id: UserId, role_id: RoleId) {
+        format!("/guilds/{}/members/{}/roles/{}", guild_id, user_id, role_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Ban>);
+
+    pub fn guild_bans_get(guild_id: GuildId, query: GuildBansQuery) {
+        format!("/guilds/{}/bans", guild_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Ban),
+    DApiPUT = ((), BanPayload),
+    DApiDELETE = ((), ());
+
+    pub fn guild_ban(guild_id: GuildId, user_id: UserId) {
+        format!("/guilds/{}/bans/{}", guild_id, user_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (BulkBanResponse, BulkBanPayload);
+
+    pub fn guild_bulk_ban(guild_id: GuildId) {
+        format!("/guilds/{}/bulk-ban", guild_id)
+    }
+}
+
+//PATCH reorders the roles
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Role>),
+    DApiPOST = (Role, RolePayload),
+    DApiPATCH = (Vec<Role>, Vec<RolePositionPayload>);
+
+    pub fn guild_roles(guild_id: GuildId) {
+        format!("/guilds/{}/roles", guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPATCH = (Role, RolePayload),
+    DApiDELETE = ((), ());
+
+    pub fn guild_role(guild_id: GuildId, role_id: RoleId) {
+        format!("/guilds/{}/roles/{}", guild_id, role_id)
     }
 }
 
+//how many members a prune would kick
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (PruneResult);
+
+    pub fn guild_prune_count(guild_id: GuildId, query: PruneQuery) {
+        format!("/guilds/{}/prune", guild_id)
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (PruneResult, PrunePayload);
+
+    pub fn guild_prune(guild_id: GuildId) {
+        format!("/guilds/{}/prune", guild_id)
+    }
+}
+
+//users
+
 dapi_endpoint! {
     version = v10,
     DApiGET = (User);
@@ -52,10 +445,63 @@ dapi_endpoint! {
 
 dapi_endpoint! {
     version = v10,
+    DApiGET = (Vec<UserGuild>);
+
+    pub fn users_me_guilds_get(query: CurrentUserGuildsQuery) {
+        "/users/@me/guilds"
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiDELETE = ((), ());
+
+    pub fn users_guilds_leave(guild_id: GuildId) {
+        format!("/users/@me/guilds/{}", guild_id)
+    }
+}
+
+//webhooks
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Webhook>),
+    DApiPOST = (Webhook, WebhookCreatePayload);
+
+    pub fn channel_webhooks(channel_id: ChannelId) {
+        format!("/channels/{}/webhooks", channel_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<Webhook>);
+
+    pub fn guild_webhooks(guild_id: GuildId) {
+        format!("/guilds/{}/webhooks", guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Webhook),
+    DApiPATCH = (Webhook, WebhookEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn webhook(webhook_id: WebhookId) {
+        format!("/webhooks/{}", webhook_id)
+    }
+}
+
+//authorized by the token in the path, these can be sent .without_auth()
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Webhook),
+    DApiPATCH = (Webhook, WebhookEditPayload),
     DApiDELETE = ((), ());
 
-    pub fn users_guilds_leave(guild_id: Snowflake<impl AsRef<str>>) {
-        format!("/users/@me/guilds/{}", guild_id.as_ref())
+    pub fn webhook_with_token(webhook_id: WebhookId, webhook_token: impl AsRef<str>) {
+        format!("/webhooks/{}/{}", webhook_id, segment(webhook_token.as_ref()))
     }
 }
 
@@ -63,10 +509,161 @@ dapi_endpoint! {
     version = v10,
     DApiPOST = (Option<Message>, MessagePayload);
 
-    pub fn webhook_execute(webhook_id: impl AsRef<str>, webhook_token: impl AsRef<str>, wait: bool, thread_id: Option<Snowflake<impl AsRef<str>>>) {
-        let p = format!("/webhooks/{}/{}?wait={}", webhook_id.as_ref(), webhook_token.as_ref(), wait);
-        if let Some(id) = thread_id {
-            p + format!("&thread_id={}", id.as_ref()).as_str()
-        } else { p }
+    pub fn webhook_execute(webhook_id: WebhookId, webhook_token: impl AsRef<str>, query: WebhookExecuteQuery) {
+        format!("/webhooks/{}/{}", webhook_id, segment(webhook_token.as_ref()))
+    } ? query
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Message),
+    DApiPATCH = (Message, MessageEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn webhook_message(webhook_id: WebhookId, webhook_token: impl AsRef<str>, message_id: MessageId, query: WebhookMessageQuery) {
+        format!("/webhooks/{}/{}/messages/{}", webhook_id, segment(webhook_token.as_ref()), message_id)
+    } ? query
+}
+
+//interactions
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = ((), InteractionResponse);
+
+    pub fn interaction_callback(interaction_id: InteractionId, interaction_token: impl AsRef<str>) {
+        format!("/interactions/{}/{}/callback", interaction_id, segment(interaction_token.as_ref()))
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Message),
+    DApiPATCH = (Message, MessageEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn interaction_original_response(application_id: ApplicationId, interaction_token: impl AsRef<str>) {
+        format!("/webhooks/{}/{}/messages/@original", application_id, segment(interaction_token.as_ref()))
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (Message, MessagePayload);
+
+    pub fn interaction_followup(application_id: ApplicationId, interaction_token: impl AsRef<str>) {
+        format!("/webhooks/{}/{}", application_id, segment(interaction_token.as_ref()))
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Message),
+    DApiPATCH = (Message, MessageEditPayload),
+    DApiDELETE = ((), ());
+
+    pub fn interaction_followup_message(application_id: ApplicationId, interaction_token: impl AsRef<str>, message_id: MessageId) {
+        format!("/webhooks/{}/{}/messages/{}", application_id, segment(interaction_token.as_ref()), message_id)
+    }
+}
+
+//application commands
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (ApplicationCommand, ApplicationCommandPayload),
+    DApiPUT = (Vec<ApplicationCommand>, Vec<ApplicationCommandPayload>);
+
+    pub fn application_commands(application_id: ApplicationId) {
+        format!("/applications/{}/commands", application_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<ApplicationCommand>);
+
+    pub fn application_commands_get(application_id: ApplicationId, with_localizations: bool) {
+        format!("/applications/{}/commands", application_id)
+    } ? ApplicationCommandsQuery { with_localizations: Some(with_localizations) }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ApplicationCommand),
+    DApiPATCH = (ApplicationCommand, ApplicationCommandPayload),
+    DApiDELETE = ((), ());
+
+    pub fn application_command(application_id: ApplicationId, command_id: CommandId) {
+        format!("/applications/{}/commands/{}", application_id, command_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiPOST = (ApplicationCommand, ApplicationCommandPayload),
+    DApiPUT = (Vec<ApplicationCommand>, Vec<ApplicationCommandPayload>);
+
+    pub fn application_guild_commands(application_id: ApplicationId, guild_id: GuildId) {
+        format!("/applications/{}/guilds/{}/commands", application_id, guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<ApplicationCommand>);
+
+    pub fn application_guild_commands_get(application_id: ApplicationId, guild_id: GuildId, with_localizations: bool) {
+        format!("/applications/{}/guilds/{}/commands", application_id, guild_id)
+    } ? ApplicationCommandsQuery { with_localizations: Some(with_localizations) }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (ApplicationCommand),
+    DApiPATCH = (ApplicationCommand, ApplicationCommandPayload),
+    DApiDELETE = ((), ());
+
+    pub fn application_guild_command(application_id: ApplicationId, guild_id: GuildId, command_id: CommandId) {
+        format!("/applications/{}/guilds/{}/commands/{}", application_id, guild_id, command_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (Vec<GuildApplicationCommandPermissions>);
+
+    pub fn application_guild_commands_permissions(application_id: ApplicationId, guild_id: GuildId) {
+        format!("/applications/{}/guilds/{}/commands/permissions", application_id, guild_id)
+    }
+}
+
+dapi_endpoint! {
+    version = v10,
+    DApiGET = (GuildApplicationCommandPermissions),
+    DApiPUT = (GuildApplicationCommandPermissions, ApplicationCommandPermissionsPayload); //needs a bearer token with applications.commands.permissions.update
+
+    pub fn application_guild_command_permissions(application_id: ApplicationId, guild_id: GuildId, command_id: CommandId) {
+        format!("/applications/{}/guilds/{}/commands/{}/permissions", application_id, guild_id, command_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        types::{GetChannelMessagesAnchorParam, GetChannelMessagesQueryBuilder},
+        *,
+    };
+    use crate::dapi::types::DApiMethod;
+
+    #[test]
+    fn message_anchors_are_flattened_into_the_query() {
+        let query = GetChannelMessagesQueryBuilder::default()
+            .anchor(GetChannelMessagesAnchorParam::Before(MessageId::new(5)))
+            .limit(10u32)
+            .build()
+            .unwrap();
+        assert_eq!(channel_messages_get(ChannelId::new(1), query).path(), "/channels/1/messages?before=5&limit=10");
+        assert_eq!(channel_messages_get(ChannelId::new(1), Default::default()).path(), "/channels/1/messages");
     }
 }
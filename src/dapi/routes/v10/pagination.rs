@@ -0,0 +1,280 @@
+#![allow(unused)]
+//the paginated listings of v10, walked with DApi::paginate
+
+use iso8601_timestamp::Timestamp;
+
+use crate::dapi::{
+    pagination::{Direction, PageBackward, PageForward, Pageable},
+    types::DApiGET,
+    versions::v10,
+};
+
+use super::{
+    super::common_types::{ChannelId, GuildId, MessageId, UserId},
+    channel_messages_get, channel_message_reactions_get, channel_threads_archived_private,
+    channel_threads_archived_public, channel_users_me_threads_archived_private, guild_bans_get, guild_members_get,
+    types::{
+        ArchivedThreads, ArchivedThreadsQuery, Ban, Channel, CurrentUserGuildsQuery, GetChannelMessagesAnchorParam,
+        GetChannelMessagesQuery, GuildBansQuery, GuildMember, GuildMembersQuery, JoinedArchivedThreadsQuery, Message,
+        ReactionsQuery, User, UserGuild,
+    },
+    users_me_guilds_get,
+};
+
+/// Newest first by default.
+pub struct ChannelMessages(pub ChannelId);
+
+pub fn channel_messages(channel_id: ChannelId) -> ChannelMessages {
+    ChannelMessages(channel_id)
+}
+
+impl Pageable<v10> for ChannelMessages {
+    type Item = Message;
+    type Cursor = MessageId;
+    type Page = Vec<Message>;
+    const DIRECTION: Direction = Direction::Before;
+
+    fn page(&self, direction: Direction, cursor: Option<MessageId>, limit: u32) -> impl DApiGET<v10, Response = Vec<Message>> + Send + 'static {
+        let anchor = cursor.map(|c| match direction {
+            Direction::Before => GetChannelMessagesAnchorParam::Before(c),
+            Direction::After => GetChannelMessagesAnchorParam::After(c),
+        });
+        channel_messages_get(self.0, GetChannelMessagesQuery { anchor, limit: Some(limit) })
+    }
+
+    fn items(page: Vec<Message>) -> (Vec<Message>, Option<bool>) {
+        (page, None)
+    }
+
+    fn cursor(item: &Message) -> Option<MessageId> {
+        Some(item.id)
+    }
+}
+
+impl PageBackward<v10> for ChannelMessages {}
+impl PageForward<v10> for ChannelMessages {}
+
+/// Ordered by user id, needs the GUILD_MEMBERS intent.
+pub struct GuildMembers(pub GuildId);
+
+pub fn guild_members(guild_id: GuildId) -> GuildMembers {
+    GuildMembers(guild_id)
+}
+
+impl Pageable<v10> for GuildMembers {
+    type Item = GuildMember;
+    type Cursor = UserId;
+    type Page = Vec<GuildMember>;
+    const DIRECTION: Direction = Direction::After;
+    const MAX_PAGE_SIZE: u32 = 1000;
+
+    fn page(&self, _: Direction, cursor: Option<UserId>, limit: u32) -> impl DApiGET<v10, Response = Vec<GuildMember>> + Send + 'static {
+        guild_members_get(self.0, GuildMembersQuery { limit: Some(limit), after: cursor })
+    }
+
+    fn items(page: Vec<GuildMember>) -> (Vec<GuildMember>, Option<bool>) {
+        (page, None)
+    }
+
+    fn cursor(item: &GuildMember) -> Option<UserId> {
+        item.user.as_ref().map(|u| u.id)
+    }
+}
+
+impl PageForward<v10> for GuildMembers {}
+
+/// Ordered by user id.
+pub struct GuildBans(pub GuildId);
+
+pub fn guild_bans(guild_id: GuildId) -> GuildBans {
+    GuildBans(guild_id)
+}
+
+impl Pageable<v10> for GuildBans {
+    type Item = Ban;
+    type Cursor = UserId;
+    type Page = Vec<Ban>;
+    const DIRECTION: Direction = Direction::After;
+    const MAX_PAGE_SIZE: u32 = 1000;
+
+    fn page(&self, direction: Direction, cursor: Option<UserId>, limit: u32) -> impl DApiGET<v10, Response = Vec<Ban>> + Send + 'static {
+        let (before, after) = match direction {
+            Direction::Before => (cursor, None),
+            Direction::After => (None, cursor),
+        };
+        guild_bans_get(self.0, GuildBansQuery { limit: Some(limit), before, after })
+    }
+
+    fn items(page: Vec<Ban>) -> (Vec<Ban>, Option<bool>) {
+        (page, None)
+    }
+
+    fn cursor(item: &Ban) -> Option<UserId> {
+        Some(item.user.id)
+    }
+}
+
+impl PageBackward<v10> for GuildBans {}
+impl PageForward<v10> for GuildBans {}
+
+/// The users who reacted with `emoji`: a unicode emoji or `name:id` for custom ones.
+pub struct MessageReactions {
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub emoji: String,
+}
+
+pub fn message_reactions(channel_id: ChannelId, message_id: MessageId, emoji: impl Into<String>) -> MessageReactions {
+    MessageReactions {
+        channel_id,
+        message_id,
+        emoji: emoji.into(),
+    }
+}
+
+impl Pageable<v10> for MessageReactions {
+    type Item = User;
+    type Cursor = UserId;
+    type Page = Vec<User>;
+    const DIRECTION: Direction = Direction::After;
+
+    fn page(&self, _: Direction, cursor: Option<UserId>, limit: u32) -> impl DApiGET<v10, Response = Vec<User>> + Send + 'static {
+        let query = ReactionsQuery { after: cursor, limit: Some(limit) };
+        channel_message_reactions_get(self.channel_id, self.message_id, self.emoji.clone(), query)
+    }
+
+    fn items(page: Vec<User>) -> (Vec<User>, Option<bool>) {
+        (page, None)
+    }
+
+    fn cursor(item: &User) -> Option<UserId> {
+        Some(item.id)
+    }
+}
+
+impl PageForward<v10> for MessageReactions {}
+
+/// Most recently archived first.
+/// Paged by archive time, and `before` skips threads archived at exactly that time, so ones sharing
+/// the archive time of the last thread of a page are left out.
+pub struct PublicArchivedThreads(pub ChannelId);
+
+pub fn public_archived_threads(channel_id: ChannelId) -> PublicArchivedThreads {
+    PublicArchivedThreads(channel_id)
+}
+
+/// Most recently archived first, needs MANAGE_THREADS.
+/// Has the same gaps at page boundaries as `PublicArchivedThreads`.
+pub struct PrivateArchivedThreads(pub ChannelId);
+
+pub fn private_archived_threads(channel_id: ChannelId) -> PrivateArchivedThreads {
+    PrivateArchivedThreads(channel_id)
+}
+
+fn archive_timestamp(thread: &Channel) -> Option<Timestamp> {
+    thread.thread_metadata.as_ref().map(|m| m.archive_timestamp)
+}
+
+impl Pageable<v10> for PublicArchivedThreads {
+    type Item = Channel;
+    type Cursor = Timestamp;
+    type Page = ArchivedThreads;
+    const DIRECTION: Direction = Direction::Before;
+
+    fn page(&self, _: Direction, cursor: Option<Timestamp>, limit: u32) -> impl DApiGET<v10, Response = ArchivedThreads> + Send + 'static {
+        channel_threads_archived_public(self.0, ArchivedThreadsQuery { before: cursor, limit: Some(limit) })
+    }
+
+    fn items(page: ArchivedThreads) -> (Vec<Channel>, Option<bool>) {
+        (page.threads, Some(page.has_more))
+    }
+
+    fn cursor(item: &Channel) -> Option<Timestamp> {
+        archive_timestamp(item)
+    }
+}
+
+impl PageBackward<v10> for PublicArchivedThreads {}
+
+impl Pageable<v10> for PrivateArchivedThreads {
+    type Item = Channel;
+    type Cursor = Timestamp;
+    type Page = ArchivedThreads;
+    const DIRECTION: Direction = Direction::Before;
+
+    fn page(&self, _: Direction, cursor: Option<Timestamp>, limit: u32) -> impl DApiGET<v10, Response = ArchivedThreads> + Send + 'static {
+        channel_threads_archived_private(self.0, ArchivedThreadsQuery { before: cursor, limit: Some(limit) })
+    }
+
+    fn items(page: ArchivedThreads) -> (Vec<Channel>, Option<bool>) {
+        (page.threads, Some(page.has_more))
+    }
+
+    fn cursor(item: &Channel) -> Option<Timestamp> {
+        archive_timestamp(item)
+    }
+}
+
+impl PageBackward<v10> for PrivateArchivedThreads {}
+
+/// Private archived threads the current user has joined, highest id first.
+pub struct JoinedPrivateArchivedThreads(pub ChannelId);
+
+pub fn joined_private_archived_threads(channel_id: ChannelId) -> JoinedPrivateArchivedThreads {
+    JoinedPrivateArchivedThreads(channel_id)
+}
+
+impl Pageable<v10> for JoinedPrivateArchivedThreads {
+    type Item = Channel;
+    type Cursor = ChannelId;
+    type Page = ArchivedThreads;
+    const DIRECTION: Direction = Direction::Before;
+
+    fn page(&self, _: Direction, cursor: Option<ChannelId>, limit: u32) -> impl DApiGET<v10, Response = ArchivedThreads> + Send + 'static {
+        channel_users_me_threads_archived_private(self.0, JoinedArchivedThreadsQuery { before: cursor, limit: Some(limit) })
+    }
+
+    fn items(page: ArchivedThreads) -> (Vec<Channel>, Option<bool>) {
+        (page.threads, Some(page.has_more))
+    }
+
+    fn cursor(item: &Channel) -> Option<ChannelId> {
+        Some(item.id)
+    }
+}
+
+impl PageBackward<v10> for JoinedPrivateArchivedThreads {}
+
+/// Ordered by guild id.
+pub struct CurrentUserGuilds;
+
+pub fn current_user_guilds() -> CurrentUserGuilds {
+    CurrentUserGuilds
+}
+
+impl Pageable<v10> for CurrentUserGuilds {
+    type Item = UserGuild;
+    type Cursor = GuildId;
+    type Page = Vec<UserGuild>;
+    const DIRECTION: Direction = Direction::After;
+    const MAX_PAGE_SIZE: u32 = 200;
+
+    fn page(&self, direction: Direction, cursor: Option<GuildId>, limit: u32) -> impl DApiGET<v10, Response = Vec<UserGuild>> + Send + 'static {
+        let (before, after) = match direction {
+            Direction::Before => (cursor, None),
+            Direction::After => (None, cursor),
+        };
+        users_me_guilds_get(CurrentUserGuildsQuery { before, after, limit: Some(limit), ..Default::default() })
+    }
+
+    fn items(page: Vec<UserGuild>) -> (Vec<UserGuild>, Option<bool>) {
+        (page, None)
+    }
+
+    fn cursor(item: &UserGuild) -> Option<GuildId> {
+        Some(item.id)
+    }
+}
+
+impl PageBackward<v10> for CurrentUserGuilds {}
+impl PageForward<v10> for CurrentUserGuilds {}
@@ -0,0 +1,262 @@
+#![allow(unused)]
+//effective permission computation as described in https://discord.com/developers/docs/topics/permissions
+
+use iso8601_timestamp::Timestamp;
+
+use crate::dapi::routes::common_types::{GuildId, RoleId, UserId};
+
+use super::types::{Channel, Guild, GuildMember, PermissionOverwrite, PermissionOverwriteType, Permissions, Role};
+
+/// Computes a member's permissions from the guild's roles and a channel's overwrites.
+pub struct PermissionCalculator<'a> {
+    guild_id: &'a GuildId,
+    owner_id: &'a UserId,
+    guild_roles: &'a [Role],
+    user_id: &'a UserId,
+    member_roles: &'a [RoleId],
+    communication_disabled_until: Option<Timestamp>,
+}
+
+impl<'a> PermissionCalculator<'a> {
+    /// `guild_roles` has to contain the @everyone role, which shares its id with the guild.
+    pub fn new(
+        guild_id: &'a GuildId,
+        owner_id: &'a UserId,
+        guild_roles: &'a [Role],
+        user_id: &'a UserId,
+        member_roles: &'a [RoleId],
+    ) -> Self {
+        Self {
+            guild_id,
+            owner_id,
+            guild_roles,
+            user_id,
+            member_roles,
+            communication_disabled_until: None,
+        }
+    }
+
+    /// Uses `guild_roles` instead of `guild.roles`, as the latter may be empty (ex. guilds from the cache).
+    /// `None` if the member object has no user.
+    pub fn for_member(guild: &'a Guild, guild_roles: &'a [Role], member: &'a GuildMember) -> Option<Self> {
+        Some(Self {
+            communication_disabled_until: member.communication_disabled_until,
+            ..Self::new(&guild.id, &guild.owner_id, guild_roles, &member.user.as_ref()?.id, &member.roles)
+        })
+    }
+
+    pub fn communication_disabled_until(mut self, until: Option<Timestamp>) -> Self {
+        self.communication_disabled_until = until;
+        self
+    }
+
+    fn is_timed_out(&self) -> bool {
+        self.communication_disabled_until
+            .is_some_and(|t| t > Timestamp::now_utc())
+    }
+
+    /// Guild-wide permissions, before any channel overwrites.
+    pub fn base(&self) -> Permissions {
+        if self.user_id == self.owner_id {
+            return Permissions::all();
+        }
+
+        let mut perms = self.guild_roles.iter()
+            .filter(|r| r.id == self.guild_id.cast() || self.member_roles.contains(&r.id))
+            .fold(Permissions::empty(), |acc, r| acc | r.permissions);
+
+        if perms.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::all();
+        }
+
+        if self.is_timed_out() {
+            perms &= Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+        }
+
+        perms
+    }
+
+    /// Permissions in `channel`. Threads take their overwrites from `parent`,
+    /// which has to be provided for them, otherwise only the base permissions apply.
+    pub fn in_channel(&self, channel: &Channel, parent: Option<&Channel>) -> Permissions {
+        let is_thread = channel.r#type.is_thread();
+        let overwrites = if is_thread {
+            parent.and_then(|p| p.permission_overwrites.as_deref())
+        } else {
+            channel.permission_overwrites.as_deref()
+        };
+
+        self.with_overwrites(overwrites.unwrap_or_default(), is_thread)
+    }
+
+    pub fn with_overwrites(&self, overwrites: &[PermissionOverwrite], is_thread: bool) -> Permissions {
+        let base = self.base();
+        if base.contains(Permissions::ADMINISTRATOR) {
+            return Permissions::all();
+        }
+
+        let mut perms = base;
+        let apply = |perms: &mut Permissions, o: &PermissionOverwrite| {
+            perms.remove(o.deny);
+            perms.insert(o.allow);
+        };
+
+        //@everyone first, then all of the member's roles at once, then the member itself
+        if let Some(o) = overwrites.iter().find(|o| o.id == self.guild_id.cast()) {
+            apply(&mut perms, o);
+        }
+
+        let (allow, deny) = overwrites.iter()
+            .filter(|o| o.r#type == PermissionOverwriteType::ROLE && self.member_roles.contains(&o.id.cast()))
+            .fold((Permissions::empty(), Permissions::empty()), |(a, d), o| (a | o.allow, d | o.deny));
+        perms.remove(deny);
+        perms.insert(allow);
+
+        if let Some(o) = overwrites.iter()
+            .find(|o| o.r#type == PermissionOverwriteType::MEMBER && o.id == self.user_id.cast()) {
+            apply(&mut perms, o);
+        }
+
+        if self.is_timed_out() {
+            perms &= Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+        }
+
+        if is_thread {
+            //sending in threads is governed by its own permission instead
+            perms.set(Permissions::SEND_MESSAGES, perms.contains(Permissions::SEND_MESSAGES_IN_THREADS));
+        }
+
+        //permissions implicitly denied by the lack of others
+        if !perms.contains(Permissions::VIEW_CHANNEL) {
+            return Permissions::empty();
+        }
+        if !perms.contains(Permissions::SEND_MESSAGES) {
+            perms.remove(
+                Permissions::SEND_TTS_MESSAGES
+                    | Permissions::MENTION_EVERYONE
+                    | Permissions::EMBED_LINKS
+                    | Permissions::ATTACH_FILES
+                    | Permissions::SEND_VOICE_MESSAGES,
+            );
+        }
+
+        perms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::dapi::routes::common_types::Snowflake;
+
+    const GUILD: GuildId = GuildId::new(1);
+    const OWNER: UserId = UserId::new(2);
+    const USER: UserId = UserId::new(3);
+    const MODS: RoleId = RoleId::new(4);
+
+    fn role(id: RoleId, permissions: Permissions) -> Role {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "name": "role",
+            "color": 0,
+            "hoist": false,
+            "icon": null,
+            "unicode_emoji": null,
+            "position": 0,
+            "permissions": permissions,
+            "managed": false,
+            "mentionable": false,
+            "flags": 0,
+        }))
+        .unwrap()
+    }
+
+    fn overwrite(id: u64, r#type: PermissionOverwriteType, allow: Permissions, deny: Permissions) -> PermissionOverwrite {
+        PermissionOverwrite { id: Snowflake::new(id), r#type, allow, deny }
+    }
+
+    fn roles() -> Vec<Role> {
+        vec![
+            role(GUILD.cast(), Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS),
+            role(MODS, Permissions::MANAGE_MESSAGES | Permissions::KICK_MEMBERS),
+        ]
+    }
+
+    #[test]
+    fn base_combines_everyone_and_member_roles() {
+        let roles = roles();
+        let member_roles = [MODS];
+        let perms = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &member_roles).base();
+        assert_eq!(
+            perms,
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS | Permissions::MANAGE_MESSAGES | Permissions::KICK_MEMBERS
+        );
+
+        let perms = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &[]).base();
+        assert!(!perms.contains(Permissions::MANAGE_MESSAGES));
+    }
+
+    #[test]
+    fn owners_and_administrators_have_everything() {
+        let mut roles = roles();
+        assert_eq!(PermissionCalculator::new(&GUILD, &OWNER, &roles, &OWNER, &[]).base(), Permissions::all());
+
+        roles.push(role(RoleId::new(5), Permissions::ADMINISTRATOR));
+        let member_roles = [RoleId::new(5)];
+        let calc = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &member_roles);
+        let deny_all = [overwrite(GUILD.get(), PermissionOverwriteType::ROLE, Permissions::empty(), Permissions::all())];
+        assert_eq!(calc.with_overwrites(&deny_all, false), Permissions::all());
+    }
+
+    #[test]
+    fn overwrites_apply_everyone_then_roles_then_member() {
+        let roles = roles();
+        let member_roles = [MODS];
+        let calc = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &member_roles);
+
+        let overwrites = [
+            overwrite(GUILD.get(), PermissionOverwriteType::ROLE, Permissions::empty(), Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS),
+            overwrite(MODS.get(), PermissionOverwriteType::ROLE, Permissions::SEND_MESSAGES, Permissions::empty()),
+            overwrite(USER.get(), PermissionOverwriteType::MEMBER, Permissions::ATTACH_FILES, Permissions::MANAGE_MESSAGES),
+        ];
+        assert_eq!(
+            calc.with_overwrites(&overwrites, false),
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES | Permissions::KICK_MEMBERS
+        );
+    }
+
+    #[test]
+    fn missing_view_or_send_takes_away_dependent_permissions() {
+        let roles = roles();
+        let calc = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &[]);
+
+        let no_view = [overwrite(GUILD.get(), PermissionOverwriteType::ROLE, Permissions::empty(), Permissions::VIEW_CHANNEL)];
+        assert_eq!(calc.with_overwrites(&no_view, false), Permissions::empty());
+
+        let no_send = [overwrite(GUILD.get(), PermissionOverwriteType::ROLE, Permissions::empty(), Permissions::SEND_MESSAGES)];
+        assert_eq!(calc.with_overwrites(&no_send, false), Permissions::VIEW_CHANNEL);
+
+        //threads need SEND_MESSAGES_IN_THREADS to send
+        assert_eq!(calc.with_overwrites(&[], true), Permissions::VIEW_CHANNEL);
+    }
+
+    #[test]
+    fn timeouts_leave_only_viewing() {
+        let roles = roles();
+        let member_roles = [MODS];
+        let calc = PermissionCalculator::new(&GUILD, &OWNER, &roles, &USER, &member_roles)
+            .communication_disabled_until(Some(Timestamp::now_utc() + iso8601_timestamp::Duration::hours(1)));
+        assert_eq!(calc.base(), Permissions::VIEW_CHANNEL);
+    }
+
+    #[test]
+    fn unknown_bits_survive_a_round_trip() {
+        let unknown = 1u64 << 60;
+        let perms: Permissions = serde_json::from_value(json!((unknown | 1 << 11).to_string())).unwrap();
+        assert!(perms.contains(Permissions::SEND_MESSAGES));
+        assert_eq!(perms.bits(), unknown | 1 << 11);
+        assert_eq!(serde_json::to_value(perms).unwrap(), json!((unknown | 1 << 11).to_string()));
+    }
+}
@@ -1,9 +1,17 @@
 #![allow(non_camel_case_types, unused)]
 
+use std::collections::HashMap;
+
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String;
-use crate::dapi::routes::common_types::{IntOrStr, Snowflake};
+use super::components::{validate_components, ActionRow, Component, ComponentType};
+use crate::dapi::cdn::{self, CdnUrl};
+use crate::dapi::multipart::{JsonObject, MultipartBody};
+use crate::dapi::routes::common_types::{
+    ApplicationId, AttachmentId, ChannelId, CommandId, EmojiId, GuildId, IntOrStr, InteractionId, MessageId, RoleId,
+    Snowflake, StickerId, TagId, UserId, WebhookId,
+};
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum EmbedType {
@@ -115,24 +123,24 @@ pub enum AllowedMentionTypes {
 #[builder(default)]
 pub struct AllowedMentions {
     pub parse: Vec<AllowedMentionTypes>,
-    pub roles: Vec<Snowflake>,
-    pub users: Vec<Snowflake>,
+    pub roles: Vec<RoleId>,
+    pub users: Vec<UserId>,
     pub replied_user: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
 #[builder(setter(strip_option, into), default)]
 pub struct MessageReference {
-    pub message_id: Option<Snowflake>,
-    pub channel_id: Option<Snowflake>,
-    pub guild_id: Option<Snowflake>,
+    pub message_id: Option<MessageId>,
+    pub channel_id: Option<ChannelId>,
+    pub guild_id: Option<GuildId>,
     pub fail_if_not_exists: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(strip_option, into))]
 pub struct Attachment {
-    pub id: Snowflake,
+    pub id: AttachmentId,
     pub filename: String,
     #[builder(default)]
     pub description: Option<String>,
@@ -167,18 +175,67 @@ bitflags::bitflags! {
 }
 
 #[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
-#[builder(default, setter(strip_option, into))]
+#[builder(default, setter(strip_option, into), build_fn(validate = "Self::validate"))]
 pub struct MessagePayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub nonce: Option<IntOrStr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tts: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub embeds: Option<Vec<Embed>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_mentions: Option<AllowedMentions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub message_reference: Option<MessageReference>,
-    //pub components: Option<Vec<MessageComponent>> ... not insane enough to type that yet
-    pub sticker_ids: Option<Vec<Snowflake>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticker_ids: Option<Vec<StickerId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<Attachment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<MessageFlags>,
+}
+
+/// The fields of `MessagePayload` an edit can change. Unset fields are left out, null would clear them.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into), build_fn(validate = "Self::validate"))]
+pub struct MessageEditPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeds: Option<Vec<Embed>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub flags: Option<MessageFlags>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<Attachment>>,
+}
+
+impl MultipartBody for MessagePayload {}
+impl MultipartBody for MessageEditPayload {}
+
+impl MessagePayloadBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        match &self.components {
+            Some(Some(c)) => validate_components(c),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl MessageEditPayloadBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        match &self.components {
+            Some(Some(c)) => validate_components(c),
+            _ => Ok(()),
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -206,7 +263,7 @@ bitflags::bitflags! {
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(strip_option, into))]
 pub struct User {
-    pub id: Snowflake,
+    pub id: UserId,
     pub username: String,
     pub discriminator: String,
     #[builder(default)]
@@ -235,11 +292,25 @@ pub struct User {
     pub public_flags: Option<UserFlags>,
 }
 
+impl User {
+    /// Falls back to the default avatar.
+    pub fn avatar_url(&self) -> CdnUrl {
+        match self.avatar {
+            Some(ref hash) => cdn::user_avatar(self.id, hash),
+            None => cdn::default_avatar(self.id, &self.discriminator),
+        }
+    }
+
+    pub fn banner_url(&self) -> Option<CdnUrl> {
+        self.banner.as_ref().map(|hash| cdn::user_banner(self.id, hash))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into))]
 pub struct ChannelMention {
-    pub id: Snowflake,
-    pub guild_id: Snowflake,
+    pub id: ChannelId,
+    pub guild_id: GuildId,
     pub r#type: u32, //not really
     pub name: String,
 }
@@ -247,9 +318,9 @@ pub struct ChannelMention {
 #[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
 #[builder(default, setter(strip_option, into))]
 pub struct Emoji {
-    pub id: Option<Snowflake>,
+    pub id: Option<EmojiId>,
     pub name: Option<String>,
-    pub roles: Option<Vec<Snowflake>>,
+    pub roles: Option<Vec<RoleId>>,
     pub user: Option<User>,
     pub require_colons: Option<bool>,
     pub managed: Option<bool>,
@@ -257,6 +328,13 @@ pub struct Emoji {
     pub available: Option<bool>,
 }
 
+impl Emoji {
+    /// `None` for unicode emojis.
+    pub fn url(&self) -> Option<CdnUrl> {
+        self.id.map(|id| cdn::emoji(id, self.animated.unwrap_or(false)))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into))]
 pub struct Reaction {
@@ -294,23 +372,48 @@ pub struct GuildMember {
     #[builder(default)]
     pub avatar: Option<String>,
     #[builder(default)]
-    pub roles: Vec<Snowflake>,
+    pub roles: Vec<RoleId>,
     pub joined_at: iso8601_timestamp::Timestamp,
     #[builder(default)]
     pub premium_since: Option<iso8601_timestamp::Timestamp>,
-    pub deaf: bool,
+    #[serde(default)]
+    pub deaf: bool, //missing on partial members, ex. in interaction resolved data
+    #[serde(default)]
     pub mute: bool,
     pub flags: GuildMemberFlags,
     #[builder(default)]
-    pub permissions: Option<String>,
+    pub permissions: Option<Permissions>,
     #[builder(default)]
     pub communication_disabled_until: Option<iso8601_timestamp::Timestamp>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VoiceState {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: Option<ChannelId>,
+    pub user_id: UserId,
+    pub member: Option<GuildMember>,
+    pub session_id: String,
+    #[serde(default)]
+    pub deaf: bool,
+    #[serde(default)]
+    pub mute: bool,
+    #[serde(default)]
+    pub self_deaf: bool,
+    #[serde(default)]
+    pub self_mute: bool,
+    pub self_stream: Option<bool>,
+    #[serde(default)]
+    pub self_video: bool,
+    #[serde(default)]
+    pub suppress: bool,
+    pub request_to_speak_timestamp: Option<iso8601_timestamp::Timestamp>
+}
+
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into, strip_option))]
 pub struct MessageInteraction {
-    pub id: Snowflake,
+    pub id: InteractionId,
     pub r#type: u32, //not really
     pub name: String,
     pub user: User,
@@ -321,11 +424,17 @@ pub struct MessageInteraction {
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into, strip_option))]
 pub struct StickerItem {
-    pub id: Snowflake,
+    pub id: StickerId,
     pub name: String,
     pub format_type: u32, //not really
 }
 
+impl StickerItem {
+    pub fn url(&self) -> CdnUrl {
+        cdn::sticker(self.id, self.format_type)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into, strip_option))]
 pub struct RoleSubscriptionData {
@@ -338,8 +447,8 @@ pub struct RoleSubscriptionData {
 #[derive(Serialize, Deserialize, Clone, Builder, Debug)]
 #[builder(setter(into, strip_option))]
 pub struct Message {
-    pub id: Snowflake,
-    pub channel_id: Snowflake,
+    pub id: MessageId,
+    pub channel_id: ChannelId,
     #[builder(default)]
     pub author: Option<User>,
     pub content: Option<String>,
@@ -351,7 +460,7 @@ pub struct Message {
     #[builder(default)]
     pub mentions: Option<Vec<User>>,
     #[builder(default)]
-    pub mention_roles: Option<Vec<Snowflake>>,
+    pub mention_roles: Option<Vec<RoleId>>,
     #[builder(default)]
     pub mention_channels: Option<Vec<ChannelMention>>,
     #[builder(default)]
@@ -364,14 +473,14 @@ pub struct Message {
     pub nonce: Option<IntOrStr>,
     pub pinned: Option<bool>,
     #[builder(default)]
-    pub webhook_id: Option<Snowflake>,
+    pub webhook_id: Option<WebhookId>,
     #[builder(default)]
     pub r#type: Option<u32>, //not really
     #[builder(default)]
     pub activity: Option<MessageActivity>,
     //pub application: Option<Application>; nope idc
     #[builder(default)]
-    pub application_id: Option<Snowflake>,
+    pub application_id: Option<ApplicationId>,
     #[builder(default)]
     pub message_reference: Option<MessageReference>,
     #[builder(default)]
@@ -381,7 +490,8 @@ pub struct Message {
     #[builder(default)]
     pub interaction: Option<MessageInteraction>,
     //pub thread: Option<Channel>, nope
-    //pub components: Option<Vec<MessageComponent>>, nope
+    #[builder(default)]
+    pub components: Option<Vec<Component>>,
     #[builder(default)]
     pub sticker_items: Option<Vec<StickerItem>>,
     //pub stickers: deprecated???
@@ -391,7 +501,7 @@ pub struct Message {
     pub role_subscription_data: Option<RoleSubscriptionData>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ChannelType(i32);
 impl ChannelType {
     pub const GUILD_TEXT: Self = Self(0);
@@ -406,14 +516,93 @@ impl ChannelType {
     pub const GUILD_STAGE_VOICE: Self = Self(13);
     pub const GUILD_DIRECTORY: Self = Self(14);
     pub const GUILD_FORUM: Self = Self(15);
+
+    pub fn is_thread(self) -> bool {
+        self == Self::ANNOUNCEMENT_THREAD || self == Self::PUBLIC_THREAD || self == Self::PRIVATE_THREAD
+    }
+}
+
+bitflags::bitflags! {
+    pub struct Permissions: u64 {
+        const CREATE_INSTANT_INVITE =               1 << 0;
+        const KICK_MEMBERS =                        1 << 1;
+        const BAN_MEMBERS =                         1 << 2;
+        const ADMINISTRATOR =                       1 << 3;
+        const MANAGE_CHANNELS =                     1 << 4;
+        const MANAGE_GUILD =                        1 << 5;
+        const ADD_REACTIONS =                       1 << 6;
+        const VIEW_AUDIT_LOG =                      1 << 7;
+        const PRIORITY_SPEAKER =                    1 << 8;
+        const STREAM =                              1 << 9;
+        const VIEW_CHANNEL =                        1 << 10;
+        const SEND_MESSAGES =                       1 << 11;
+        const SEND_TTS_MESSAGES =                   1 << 12;
+        const MANAGE_MESSAGES =                     1 << 13;
+        const EMBED_LINKS =                         1 << 14;
+        const ATTACH_FILES =                        1 << 15;
+        const READ_MESSAGE_HISTORY =                1 << 16;
+        const MENTION_EVERYONE =                    1 << 17;
+        const USE_EXTERNAL_EMOJIS =                 1 << 18;
+        const VIEW_GUILD_INSIGHTS =                 1 << 19;
+        const CONNECT =                             1 << 20;
+        const SPEAK =                               1 << 21;
+        const MUTE_MEMBERS =                        1 << 22;
+        const DEAFEN_MEMBERS =                      1 << 23;
+        const MOVE_MEMBERS =                        1 << 24;
+        const USE_VAD =                             1 << 25;
+        const CHANGE_NICKNAME =                     1 << 26;
+        const MANAGE_NICKNAMES =                    1 << 27;
+        const MANAGE_ROLES =                        1 << 28;
+        const MANAGE_WEBHOOKS =                     1 << 29;
+        const MANAGE_GUILD_EXPRESSIONS =            1 << 30;
+        const USE_APPLICATION_COMMANDS =            1 << 31;
+        const REQUEST_TO_SPEAK =                    1 << 32;
+        const MANAGE_EVENTS =                       1 << 33;
+        const MANAGE_THREADS =                      1 << 34;
+        const CREATE_PUBLIC_THREADS =               1 << 35;
+        const CREATE_PRIVATE_THREADS =              1 << 36;
+        const USE_EXTERNAL_STICKERS =               1 << 37;
+        const SEND_MESSAGES_IN_THREADS =            1 << 38;
+        const USE_EMBEDDED_ACTIVITIES =             1 << 39;
+        const MODERATE_MEMBERS =                    1 << 40;
+        const VIEW_CREATOR_MONETIZATION_ANALYTICS = 1 << 41;
+        const USE_SOUNDBOARD =                      1 << 42;
+        const USE_EXTERNAL_SOUNDS =                 1 << 45;
+        const SEND_VOICE_MESSAGES =                 1 << 46;
+    }
+}
+
+impl Serialize for Permissions { //discord sends and expects these as strings, they don't fit in a js number
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = match IntOrStr::deserialize(deserializer)? {
+            IntOrStr::Integer(i) => i as u64,
+            IntOrStr::String(s) => s.parse().map_err(serde::de::Error::custom)?,
+        };
+        //unknown bits are newer permissions we don't know about yet, they're kept so they survive being sent back.
+        //bitflags 1 has no from_bits_retain, the struct is ours to build though
+        Ok(Self { bits })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PermissionOverwriteType(u8);
+impl PermissionOverwriteType {
+    pub const ROLE: Self = Self(0);
+    pub const MEMBER: Self = Self(1);
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PermissionOverwrite {
     pub id: Snowflake,
-    pub r#type: i8,
-    pub allow: String,
-    pub deny: String
+    pub r#type: PermissionOverwriteType,
+    pub allow: Permissions,
+    pub deny: Permissions
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -428,8 +617,8 @@ pub struct ThreadMetadata {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ThreadMember {
-    pub id: Option<Snowflake>,
-    pub user_id: Option<Snowflake>,
+    pub id: Option<ChannelId>,
+    pub user_id: Option<UserId>,
     pub join_timestamp: iso8601_timestamp::Timestamp,
     pub flags: u64,
     pub member: Option<GuildMember>
@@ -446,58 +635,229 @@ bitflags::bitflags! {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ForumTag {
-    pub id: Snowflake,
+    pub id: TagId,
     pub name: String,
     pub moderated: bool,
-    pub emoji_id: Option<Snowflake>,
+    pub emoji_id: Option<EmojiId>,
     pub emoji_name: Option<String>
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct DefaultReaction {
-    pub emoji_id: Option<Snowflake>,
+    pub emoji_id: Option<EmojiId>,
     pub emoji_name: Option<String>
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Channel {
-    pub id: Snowflake,
+    pub id: ChannelId,
     pub r#type: ChannelType,
-    pub guild_id: Option<Snowflake>,
+    pub guild_id: Option<GuildId>,
     pub position: Option<i32>,
     pub permission_overwrites: Option<Vec<PermissionOverwrite>>,
     pub name: Option<String>,
     pub topic: Option<String>,
     pub nsfw: Option<bool>,
-    pub last_message_id: Option<Snowflake>,
+    pub last_message_id: Option<MessageId>,
     pub bitrate: Option<i32>,
     pub user_limit: Option<i32>,
     pub rate_limit_per_user: Option<i32>,
     pub recipients: Option<Vec<User>>,
     pub icon: Option<String>,
-    pub owner_id: Option<Snowflake>,
-    pub application_id: Option<Snowflake>,
+    pub owner_id: Option<UserId>,
+    pub application_id: Option<ApplicationId>,
     pub managed: Option<bool>,
-    pub parent_id: Option<Snowflake>,
+    pub parent_id: Option<ChannelId>,
     pub last_pin_timestamp: Option<iso8601_timestamp::Timestamp>,
     pub rtc_region: Option<String>,
     pub video_quality_mode: Option<i32>,
     pub message_count: Option<i64>,
     pub member_count: Option<i32>,
     pub thread_metadata: Option<ThreadMetadata>,
-    pub member: Option<GuildMember>,
+    pub member: Option<ThreadMember>,
     pub default_auto_archive_duration: Option<i32>,
-    pub permissions: Option<String>,
+    pub permissions: Option<Permissions>,
     pub flags: Option<ChannelFlags>,
     pub total_message_sent: Option<i64>,
     pub available_tags: Option<Vec<ForumTag>>,
-    pub applied_tags: Option<Vec<Snowflake>>,
+    pub applied_tags: Option<Vec<TagId>>,
     pub default_reaction_emoji: Option<DefaultReaction>,
     pub default_thread_rate_limit_per_user: Option<i32>,
     pub default_sort_order: Option<i32>,
     pub default_forum_layout: Option<i32>
 }
 
+/// Only the set fields are changed. The nested options clear the value when set to `None`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct ChannelEditPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<ChannelType>, //only text <-> announcement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_overwrites: Option<Vec<PermissionOverwrite>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtc_region: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_quality_mode: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_auto_archive_duration: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<ChannelFlags>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_tags: Option<Vec<ForumTag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_reaction_emoji: Option<Option<DefaultReaction>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_thread_rate_limit_per_user: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_sort_order: Option<Option<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_forum_layout: Option<i32>,
+    //threads only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_tags: Option<Vec<TagId>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct PermissionOverwritePayload {
+    pub r#type: PermissionOverwriteType,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Permissions>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny: Option<Permissions>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BulkDeletePayload {
+    pub messages: Vec<MessageId>,
+}
+
+impl BulkDeletePayload {
+    /// Discord refuses to bulk delete messages older than 2 weeks.
+    pub const MAX_AGE_MS: u64 = 14 * 24 * 60 * 60 * 1000;
+
+    /// Leaves out messages too old to be bulk deleted (with a minute of leeway), they have to be deleted one by one.
+    /// The request still needs between 2 and 100 messages.
+    pub fn recent(messages: impl IntoIterator<Item = MessageId>) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let oldest = now.saturating_sub(Self::MAX_AGE_MS - 60 * 1000);
+        Self {
+            messages: messages.into_iter().filter(|m| m.timestamp_ms() >= oldest).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct StartThreadFromMessagePayload {
+    pub name: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct StartThreadPayload {
+    pub name: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<ChannelType>, //defaults to PRIVATE_THREAD
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitable: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct StartForumThreadPayload {
+    pub name: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_archive_duration: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<i32>,
+    pub message: MessagePayload,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_tags: Option<Vec<TagId>>,
+}
+
+/// A thread started in a forum channel, along with its starter message.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ForumThread {
+    pub message: Option<Message>,
+    #[serde(flatten)]
+    pub channel: Channel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActiveThreads {
+    pub threads: Vec<Channel>,
+    pub members: Vec<ThreadMember>, //only for the threads the current user has joined
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArchivedThreads {
+    pub threads: Vec<Channel>,
+    pub members: Vec<ThreadMember>,
+    pub has_more: bool,
+}
+
+impl ArchivedThreads {
+    /// The `before` value for the next page of public or private archived threads, `None` on the last page.
+    pub fn next_before(&self) -> Option<iso8601_timestamp::Timestamp> {
+        if !self.has_more {
+            return None;
+        }
+        self.threads.last()?.thread_metadata.as_ref().map(|m| m.archive_timestamp)
+    }
+
+    /// The `before` value for the next page of joined private archived threads, `None` on the last page.
+    pub fn next_before_id(&self) -> Option<ChannelId> {
+        self.has_more.then(|| self.threads.last().map(|t| t.id)).flatten()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct VerificationLevel(u8);
 impl VerificationLevel {
@@ -525,20 +885,26 @@ impl ExplicitContentFilterLevel {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Role {
-    pub id: Snowflake,
+    pub id: RoleId,
     pub name: String,
     pub color: i32,
     pub hoist: bool,
     pub icon: Option<String>,
     pub unicode_emoji: Option<String>,
     pub position: i32,
-    pub permissions: String,
+    pub permissions: Permissions,
     pub managed: bool,
     pub mentionable: bool,
     //pub tags: Option<>, a fucked up object
     pub flags: u32
 }
 
+impl Role {
+    pub fn icon_url(&self) -> Option<CdnUrl> {
+        self.icon.as_ref().map(|hash| cdn::role_icon(self.id, hash))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct MfaLevel(u8);
 impl MfaLevel {
@@ -548,20 +914,20 @@ impl MfaLevel {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Guild {
-    pub id: Snowflake,
+    pub id: GuildId,
     pub name: String,
     pub icon: Option<String>,
     pub icon_hash: Option<String>,
     pub splash: Option<String>,
     pub discovery_splash: Option<String>,
     pub owner: Option<bool>,
-    pub owner_id: Snowflake,
-    pub permissions: Option<String>,
+    pub owner_id: UserId,
+    pub permissions: Option<Permissions>,
     pub region: Option<String>,
-    pub afk_channel_id: Option<Snowflake>,
+    pub afk_channel_id: Option<ChannelId>,
     pub afk_timeout: i32,
     pub widget_enabled: Option<bool>,
-    pub widget_channel_id: Option<Snowflake>,
+    pub widget_channel_id: Option<ChannelId>,
     pub verification_level: VerificationLevel,
     pub default_message_notifications: DefaultMessageNotificationLevel,
     pub explicit_content_filter: ExplicitContentFilterLevel,
@@ -569,7 +935,1054 @@ pub struct Guild {
     pub emojis: Vec<Emoji>,
     pub features: Vec<String>,
     pub mfa_level: MfaLevel,
-    pub application_id: Option<Snowflake>,
-    pub system_channel_id: Option<Snowflake>,
+    pub application_id: Option<ApplicationId>,
+    pub system_channel_id: Option<ChannelId>,
+    pub system_channel_flags: Option<SystemChannelFlags>,
+    pub rules_channel_id: Option<ChannelId>,
+    pub public_updates_channel_id: Option<ChannelId>,
+    pub safety_alerts_channel_id: Option<ChannelId>,
+    pub max_members: Option<u32>,
+    pub vanity_url_code: Option<String>,
+    pub description: Option<String>,
+    pub banner: Option<String>,
+    pub premium_tier: Option<u8>,
+    pub premium_subscription_count: Option<u32>,
+    pub preferred_locale: Option<String>,
+    pub nsfw_level: Option<u8>,
+    pub premium_progress_bar_enabled: Option<bool>,
+    /// Only with `guild_get(.., true)`.
+    pub approximate_member_count: Option<u32>,
+    pub approximate_presence_count: Option<u32>,
     //....rest https://discord.com/developers/docs/resources/guild#guild-object
-}
\ No newline at end of file
+}
+
+impl Guild {
+    pub fn icon_url(&self) -> Option<CdnUrl> {
+        self.icon.as_ref().map(|hash| cdn::guild_icon(self.id, hash))
+    }
+
+    pub fn splash_url(&self) -> Option<CdnUrl> {
+        self.splash.as_ref().map(|hash| cdn::guild_splash(self.id, hash))
+    }
+
+    pub fn banner_url(&self) -> Option<CdnUrl> {
+        self.banner.as_ref().map(|hash| cdn::guild_banner(self.id, hash))
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct SystemChannelFlags: u32 {
+        const SUPPRESS_JOIN_NOTIFICATIONS = 1 << 0;
+        const SUPPRESS_PREMIUM_SUBSCRIPTIONS = 1 << 1;
+        const SUPPRESS_GUILD_REMINDER_NOTIFICATIONS = 1 << 2;
+        const SUPPRESS_JOIN_NOTIFICATION_REPLIES = 1 << 3;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuildPreview {
+    pub id: GuildId,
+    pub name: String,
+    pub icon: Option<String>,
+    pub splash: Option<String>,
+    pub discovery_splash: Option<String>,
+    pub emojis: Vec<Emoji>,
+    pub features: Vec<String>,
+    pub approximate_member_count: u32,
+    pub approximate_presence_count: u32,
+    pub description: Option<String>,
+}
+
+/// Only the set fields are changed. The nested options clear the value when set to `None`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct GuildEditPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_level: Option<Option<VerificationLevel>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_message_notifications: Option<Option<DefaultMessageNotificationLevel>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explicit_content_filter: Option<Option<ExplicitContentFilterLevel>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub afk_channel_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub afk_timeout: Option<i32>,
+    /// Image data URI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<UserId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub splash: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discovery_splash: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_channel_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_channel_flags: Option<SystemChannelFlags>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules_channel_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_updates_channel_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_alerts_channel_id: Option<Option<ChannelId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_locale: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub premium_progress_bar_enabled: Option<bool>,
+}
+
+/// A guild as listed for the current user, see `users_me_guilds_get`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserGuild {
+    pub id: GuildId,
+    pub name: String,
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub owner: bool,
+    pub permissions: Option<Permissions>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub approximate_member_count: Option<u32>,
+    pub approximate_presence_count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Ban {
+    pub reason: Option<String>,
+    pub user: User,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct BanPayload {
+    /// Deletes the user's messages from this many seconds back, up to 604800 (7 days).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_message_seconds: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct BulkBanPayload {
+    /// Up to 200 users.
+    pub user_ids: Vec<UserId>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_message_seconds: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BulkBanResponse {
+    pub banned_users: Vec<UserId>,
+    pub failed_users: Vec<UserId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct ChannelCreatePayload {
+    pub name: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<ChannelType>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_user: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_overwrites: Option<Vec<PermissionOverwrite>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<ChannelId>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtc_region: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_quality_mode: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_auto_archive_duration: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_reaction_emoji: Option<DefaultReaction>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_tags: Option<Vec<ForumTag>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_sort_order: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_forum_layout: Option<i32>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_thread_rate_limit_per_user: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChannelPositionPayload {
+    pub id: ChannelId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+    /// Syncs the permissions with the new parent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_permissions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Option<ChannelId>>,
+}
+
+/// Only the set fields are changed. The nested options clear the value when set to `None`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into), build_fn(validate = "Self::validate"))]
+pub struct GuildMemberEditPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nick: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<RoleId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deaf: Option<bool>,
+    /// Moves the member to another voice channel, `None` disconnects them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Option<ChannelId>>,
+    /// Times the member out until then, `None` lifts the timeout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub communication_disabled_until: Option<Option<iso8601_timestamp::Timestamp>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<GuildMemberFlags>,
+}
+
+impl GuildMemberEditPayload {
+    /// Timeouts can't be longer than 28 days.
+    pub const MAX_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(28 * 24 * 60 * 60);
+
+    /// Times the member out for `duration` from now, capped at `MAX_TIMEOUT`.
+    pub fn timeout(duration: std::time::Duration) -> Self {
+        let secs = duration.min(Self::MAX_TIMEOUT).as_secs() as i64;
+        Self {
+            communication_disabled_until: Some(Some(
+                iso8601_timestamp::Timestamp::now_utc() + iso8601_timestamp::Duration::seconds(secs),
+            )),
+            ..Default::default()
+        }
+    }
+
+    pub fn remove_timeout() -> Self {
+        Self {
+            communication_disabled_until: Some(None),
+            ..Default::default()
+        }
+    }
+}
+
+impl GuildMemberEditPayloadBuilder {
+    fn validate(&self) -> Result<(), std::string::String> {
+        let max = iso8601_timestamp::Timestamp::now_utc()
+            + iso8601_timestamp::Duration::seconds(GuildMemberEditPayload::MAX_TIMEOUT.as_secs() as i64);
+        match &self.communication_disabled_until {
+            Some(Some(Some(until))) if *until > max => Err("timeouts can't be longer than 28 days".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Only the set fields are changed. The nested options clear the value when set to `None`.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct RolePayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Permissions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hoist: Option<bool>,
+    /// Image data URI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unicode_emoji: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentionable: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RolePositionPayload {
+    pub id: RoleId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct PrunePayload {
+    /// 1-30, 7 by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    /// Discord recommends turning this off for large guilds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_prune_count: Option<bool>,
+    /// Roles whose members are pruned too, members with roles are left alone by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_roles: Option<Vec<RoleId>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PruneResult {
+    /// `None` when `compute_prune_count` was off.
+    pub pruned: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WebhookType(i32);
+impl WebhookType {
+    pub const INCOMING: Self = Self(1);
+    pub const CHANNEL_FOLLOWER: Self = Self(2);
+    pub const APPLICATION: Self = Self(3);
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub r#type: WebhookType,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: Option<ChannelId>,
+    /// Missing when fetched with the token.
+    pub user: Option<User>,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+    /// Only incoming webhooks have one.
+    pub token: Option<String>,
+    pub application_id: Option<ApplicationId>,
+    /// For channel follower webhooks.
+    pub source_guild: Option<WebhookSourceGuild>,
+    pub source_channel: Option<WebhookSourceChannel>,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookSourceGuild {
+    pub id: GuildId,
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookSourceChannel {
+    pub id: ChannelId,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct WebhookCreatePayload {
+    /// 1-80 characters, can't contain "clyde" or "discord".
+    pub name: String,
+    /// Image data URI.
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+/// Only the set fields are changed, `avatar: Some(None)` removes the avatar.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct WebhookEditPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<Option<String>>,
+    /// Can't be changed through `webhook_with_token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<ChannelId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InteractionType(u8);
+impl InteractionType {
+    pub const PING: Self = Self(1);
+    pub const APPLICATION_COMMAND: Self = Self(2);
+    pub const MESSAGE_COMPONENT: Self = Self(3);
+    pub const APPLICATION_COMMAND_AUTOCOMPLETE: Self = Self(4);
+    pub const MODAL_SUBMIT: Self = Self(5);
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ApplicationCommandType(u8);
+impl ApplicationCommandType {
+    pub const CHAT_INPUT: Self = Self(1);
+    pub const USER: Self = Self(2);
+    pub const MESSAGE: Self = Self(3);
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ApplicationCommandOptionType(u8);
+impl ApplicationCommandOptionType {
+    pub const SUB_COMMAND: Self = Self(1);
+    pub const SUB_COMMAND_GROUP: Self = Self(2);
+    pub const STRING: Self = Self(3);
+    pub const INTEGER: Self = Self(4);
+    pub const BOOLEAN: Self = Self(5);
+    pub const USER: Self = Self(6);
+    pub const CHANNEL: Self = Self(7);
+    pub const ROLE: Self = Self(8);
+    pub const MENTIONABLE: Self = Self(9);
+    pub const NUMBER: Self = Self(10);
+    pub const ATTACHMENT: Self = Self(11);
+}
+
+/// Value of a command option, ids of users, channels, roles etc. arrive as strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ApplicationCommandOptionValue {
+    Boolean(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+}
+
+impl ApplicationCommandOptionValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            Self::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// For USER, CHANNEL, ROLE, MENTIONABLE and ATTACHMENT options.
+    pub fn as_id<M>(&self) -> Option<Snowflake<M>> {
+        self.as_str()?.parse().ok()
+    }
+}
+
+/// Locale (ex. `en-US`, `pl`) to translated string.
+pub type Localizations = HashMap<String, String>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApplicationCommandOptionChoice {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_localizations: Option<Localizations>,
+    pub value: ApplicationCommandOptionValue,
+}
+
+impl ApplicationCommandOptionChoice {
+    pub fn new(name: impl Into<String>, value: ApplicationCommandOptionValue) -> Self {
+        Self { name: name.into(), name_localizations: None, value }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Builder, Debug, PartialEq)]
+#[builder(setter(strip_option, into))]
+pub struct ApplicationCommandOption {
+    pub r#type: ApplicationCommandOptionType,
+    pub name: String,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_localizations: Option<Localizations>,
+    pub description: String,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_localizations: Option<Localizations>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<ApplicationCommandOptionChoice>>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<ApplicationCommandOption>>, //for subcommands and groups
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_types: Option<Vec<ChannelType>>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<serde_json::Number>, //integer or double, depending on the option type
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<serde_json::Number>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u16>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u16>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autocomplete: Option<bool>,
+}
+
+impl ApplicationCommandOption {
+    /// Fills in what discord leaves out when the value is the default one, so options can be compared.
+    pub fn normalized(&self) -> Self {
+        Self {
+            name_localizations: self.name_localizations.clone().filter(|l| !l.is_empty()),
+            description_localizations: self.description_localizations.clone().filter(|l| !l.is_empty()),
+            required: Some(self.required.unwrap_or(false)),
+            choices: self.choices.clone().filter(|c| !c.is_empty()),
+            options: self.options.as_ref()
+                .map(|o| o.iter().map(Self::normalized).collect::<Vec<_>>())
+                .filter(|o| !o.is_empty()),
+            channel_types: self.channel_types.clone().filter(|c| !c.is_empty()),
+            autocomplete: Some(self.autocomplete.unwrap_or(false)),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationCommand {
+    pub id: CommandId,
+    pub r#type: Option<ApplicationCommandType>, //CHAT_INPUT if missing
+    pub application_id: ApplicationId,
+    pub guild_id: Option<GuildId>,
+    pub name: String,
+    pub name_localizations: Option<Localizations>,
+    pub description: String, //empty for USER and MESSAGE commands
+    pub description_localizations: Option<Localizations>,
+    pub options: Option<Vec<ApplicationCommandOption>>,
+    pub default_member_permissions: Option<Permissions>,
+    pub dm_permission: Option<bool>,
+    pub nsfw: Option<bool>,
+    pub version: Snowflake, //bumped on every update
+}
+
+/// Body for creating, editing and bulk overwriting commands.
+#[derive(Serialize, Deserialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct ApplicationCommandPayload {
+    pub name: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_localizations: Option<Localizations>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, //required for CHAT_INPUT
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_localizations: Option<Localizations>,
+    #[builder(default, setter(each(name = "option", into)))]
+    #[serde(default)] //always sent, an edit without options keeps the old ones
+    pub options: Vec<ApplicationCommandOption>,
+    #[builder(default)]
+    pub default_member_permissions: Option<Permissions>, //null means everyone, an empty set means admins only
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dm_permission: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<ApplicationCommandType>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+}
+
+impl ApplicationCommandPayload {
+    pub fn command_type(&self) -> ApplicationCommandType {
+        self.r#type.unwrap_or(ApplicationCommandType::CHAT_INPUT)
+    }
+
+    /// Whether `registered` is what this payload would produce, ignoring defaults discord fills in or leaves out.
+    pub fn matches(&self, registered: &ApplicationCommand) -> bool {
+        let localizations = |l: &Option<Localizations>| l.clone().filter(|l| !l.is_empty());
+        let options = |o: &[ApplicationCommandOption]| o.iter().map(ApplicationCommandOption::normalized).collect::<Vec<_>>();
+
+        self.name == registered.name
+            && self.command_type() == registered.r#type.unwrap_or(ApplicationCommandType::CHAT_INPUT)
+            && self.description.as_deref().unwrap_or_default() == registered.description
+            && localizations(&self.name_localizations) == localizations(&registered.name_localizations)
+            && localizations(&self.description_localizations) == localizations(&registered.description_localizations)
+            && options(&self.options) == options(registered.options.as_deref().unwrap_or_default())
+            && self.default_member_permissions == registered.default_member_permissions
+            && (registered.guild_id.is_some() || self.dm_permission.unwrap_or(true) == registered.dm_permission.unwrap_or(true))
+            && self.nsfw.unwrap_or(false) == registered.nsfw.unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ApplicationCommandPermissionType(u8);
+impl ApplicationCommandPermissionType {
+    pub const ROLE: Self = Self(1);
+    pub const USER: Self = Self(2);
+    pub const CHANNEL: Self = Self(3);
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationCommandPermission {
+    pub id: Snowflake, //role, user or channel, the guild id is @everyone and the guild id - 1 is all channels
+    pub r#type: ApplicationCommandPermissionType,
+    pub permission: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuildApplicationCommandPermissions {
+    pub id: Snowflake, //the command, or the application for app-wide defaults
+    pub application_id: ApplicationId,
+    pub guild_id: GuildId,
+    pub permissions: Vec<ApplicationCommandPermission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationCommandPermissionsPayload {
+    pub permissions: Vec<ApplicationCommandPermission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationCommandInteractionDataOption {
+    pub name: String,
+    pub r#type: ApplicationCommandOptionType,
+    pub value: Option<ApplicationCommandOptionValue>,
+    pub options: Option<Vec<ApplicationCommandInteractionDataOption>>, //for subcommands and groups
+    pub focused: Option<bool>, //the option being autocompleted
+}
+
+/// Full objects of everything referenced by the options or the target of a command.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ResolvedData {
+    pub users: HashMap<UserId, User>,
+    pub members: HashMap<UserId, GuildMember>, //partial, no user, deaf or mute
+    pub roles: HashMap<RoleId, Role>,
+    pub channels: HashMap<ChannelId, Channel>, //partial
+    pub messages: HashMap<MessageId, Message>,
+    pub attachments: HashMap<AttachmentId, Attachment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApplicationCommandData {
+    pub id: CommandId,
+    pub name: String,
+    pub r#type: ApplicationCommandType,
+    pub resolved: Option<ResolvedData>,
+    pub options: Option<Vec<ApplicationCommandInteractionDataOption>>,
+    pub guild_id: Option<GuildId>,
+    pub target_id: Option<Snowflake>, //user or message id for context menu commands
+}
+
+impl ApplicationCommandData {
+    /// Top-level option by name, subcommand options have to be looked up through their `options`.
+    pub fn option(&self, name: &str) -> Option<&ApplicationCommandInteractionDataOption> {
+        self.options.as_ref()?.iter().find(|o| o.name == name)
+    }
+
+    /// The option that is being autocompleted, searched through subcommands as well.
+    pub fn focused_option(&self) -> Option<&ApplicationCommandInteractionDataOption> {
+        fn find(options: &[ApplicationCommandInteractionDataOption]) -> Option<&ApplicationCommandInteractionDataOption> {
+            options.iter().find_map(|o| {
+                if o.focused == Some(true) {
+                    Some(o)
+                } else {
+                    find(o.options.as_deref()?)
+                }
+            })
+        }
+        find(self.options.as_deref()?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageComponentData {
+    pub custom_id: String,
+    pub component_type: ComponentType,
+    pub values: Option<Vec<String>>, //select menus only
+    pub resolved: Option<ResolvedData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModalSubmitData {
+    pub custom_id: String,
+    pub components: Vec<ActionRow>, //with the submitted text inputs
+}
+
+impl ModalSubmitData {
+    /// Submitted value of the text input with `custom_id`.
+    pub fn value(&self, custom_id: &str) -> Option<&str> {
+        self.components.iter().find_map(|r| r.text_input_value(custom_id))
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum InteractionData {
+    ApplicationCommand(Box<ApplicationCommandData>), //autocomplete as well
+    MessageComponent(Box<MessageComponentData>),
+    ModalSubmit(ModalSubmitData),
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Interaction {
+    pub id: InteractionId,
+    pub application_id: ApplicationId,
+    pub r#type: InteractionType,
+    pub data: Option<InteractionData>,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: Option<ChannelId>,
+    pub member: Option<GuildMember>, //in guilds
+    pub user: Option<User>, //in DMs
+    pub token: String,
+    pub version: i32,
+    pub message: Option<Message>, //for components
+    pub app_permissions: Option<Permissions>,
+    pub locale: Option<String>,
+    pub guild_locale: Option<String>,
+}
+
+impl Interaction {
+    /// The invoking user, whether the interaction happened in a guild or in DMs.
+    pub fn author(&self) -> Option<&User> {
+        self.member.as_ref().and_then(|m| m.user.as_ref()).or(self.user.as_ref())
+    }
+
+    pub fn command_data(&self) -> Option<&ApplicationCommandData> {
+        match &self.data {
+            Some(InteractionData::ApplicationCommand(d)) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn component_data(&self) -> Option<&MessageComponentData> {
+        match &self.data {
+            Some(InteractionData::MessageComponent(d)) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn modal_data(&self) -> Option<&ModalSubmitData> {
+        match &self.data {
+            Some(InteractionData::ModalSubmit(d)) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Interaction { //data's shape depends on the interaction type
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct InteractionProxy<'a> {
+            id: InteractionId,
+            application_id: ApplicationId,
+            r#type: InteractionType,
+            #[serde(borrow)]
+            data: Option<&'a serde_json::value::RawValue>,
+            guild_id: Option<GuildId>,
+            channel_id: Option<ChannelId>,
+            member: Option<GuildMember>,
+            user: Option<User>,
+            token: String,
+            version: i32,
+            message: Option<Message>,
+            app_permissions: Option<Permissions>,
+            locale: Option<String>,
+            guild_locale: Option<String>,
+        }
+
+        let i = InteractionProxy::deserialize(deserializer)?;
+
+        macro_rules! inner {
+            ($d:expr) => { serde_json::from_str($d.get()).map_err(serde::de::Error::custom)? };
+        }
+        use {InteractionData as ID, InteractionType as IT};
+
+        let data = match (i.r#type, i.data) {
+            (IT::APPLICATION_COMMAND | IT::APPLICATION_COMMAND_AUTOCOMPLETE, Some(d)) => Some(ID::ApplicationCommand(inner!(d))),
+            (IT::MESSAGE_COMPONENT, Some(d)) =>                                          Some(ID::MessageComponent(inner!(d))),
+            (IT::MODAL_SUBMIT, Some(d)) =>                                               Some(ID::ModalSubmit(inner!(d))),
+            _ => None,
+        };
+
+        Ok(Self {
+            id: i.id,
+            application_id: i.application_id,
+            r#type: i.r#type,
+            data,
+            guild_id: i.guild_id,
+            channel_id: i.channel_id,
+            member: i.member,
+            user: i.user,
+            token: i.token,
+            version: i.version,
+            message: i.message,
+            app_permissions: i.app_permissions,
+            locale: i.locale,
+            guild_locale: i.guild_locale,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InteractionCallbackType(u8);
+impl InteractionCallbackType {
+    pub const PONG: Self = Self(1);
+    pub const CHANNEL_MESSAGE_WITH_SOURCE: Self = Self(4);
+    pub const DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE: Self = Self(5);
+    pub const DEFERRED_UPDATE_MESSAGE: Self = Self(6); //components only
+    pub const UPDATE_MESSAGE: Self = Self(7); //components only
+    pub const APPLICATION_COMMAND_AUTOCOMPLETE_RESULT: Self = Self(8);
+    pub const MODAL: Self = Self(9);
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutocompleteCallbackData {
+    pub choices: Vec<ApplicationCommandOptionChoice>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModalCallbackData {
+    pub custom_id: String,
+    pub title: String,
+    pub components: Vec<ActionRow>, //of text inputs
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum InteractionCallbackData {
+    Modal(ModalCallbackData),
+    Autocomplete(AutocompleteCallbackData),
+    Message(Box<MessagePayload>), //has to stay last, every field is optional
+}
+
+/// The initial response to an interaction, has to be sent within 3 seconds of receiving it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InteractionResponse {
+    pub r#type: InteractionCallbackType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<InteractionCallbackData>,
+}
+
+//the files belong to the message in `data`
+impl MultipartBody for InteractionResponse {
+    fn attachments_parent(payload: &mut JsonObject) -> Option<&mut JsonObject> {
+        payload.get_mut("data")?.as_object_mut()
+    }
+}
+
+impl InteractionResponse {
+    pub fn pong() -> Self {
+        Self { r#type: InteractionCallbackType::PONG, data: None }
+    }
+
+    pub fn message(msg: MessagePayload) -> Self {
+        Self {
+            r#type: InteractionCallbackType::CHANNEL_MESSAGE_WITH_SOURCE,
+            data: Some(InteractionCallbackData::Message(Box::new(msg))),
+        }
+    }
+
+    /// Shows a loading state, the message is then sent by editing the original response.
+    pub fn deferred(ephemeral: bool) -> Self {
+        Self {
+            r#type: InteractionCallbackType::DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE,
+            data: ephemeral.then(|| InteractionCallbackData::Message(Box::new(MessagePayload {
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..Default::default()
+            }))),
+        }
+    }
+
+    pub fn update_message(msg: MessagePayload) -> Self {
+        Self {
+            r#type: InteractionCallbackType::UPDATE_MESSAGE,
+            data: Some(InteractionCallbackData::Message(Box::new(msg))),
+        }
+    }
+
+    pub fn deferred_update_message() -> Self {
+        Self { r#type: InteractionCallbackType::DEFERRED_UPDATE_MESSAGE, data: None }
+    }
+
+    pub fn autocomplete(choices: Vec<ApplicationCommandOptionChoice>) -> Self {
+        Self {
+            r#type: InteractionCallbackType::APPLICATION_COMMAND_AUTOCOMPLETE_RESULT,
+            data: Some(InteractionCallbackData::Autocomplete(AutocompleteCallbackData { choices })),
+        }
+    }
+
+    pub fn modal(modal: ModalCallbackData) -> Self {
+        Self {
+            r#type: InteractionCallbackType::MODAL,
+            data: Some(InteractionCallbackData::Modal(modal)),
+        }
+    }
+}
+
+//query strings, unset fields are left out
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct GetChannelMessagesQuery {
+    /// The latest messages when unset.
+    #[serde(flatten)]
+    pub anchor: Option<GetChannelMessagesAnchorParam>,
+    /// 1-100, 50 by default.
+    pub limit: Option<u32>,
+}
+
+/// Which messages to get, only one of these can be sent.
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GetChannelMessagesAnchorParam {
+    Around(MessageId),
+    Before(MessageId),
+    After(MessageId),
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct WebhookExecuteQuery {
+    /// Waits for the message to be sent and returns it.
+    pub wait: Option<bool>,
+    pub thread_id: Option<ChannelId>,
+}
+
+/// For messages sent to a thread by the webhook.
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct WebhookMessageQuery {
+    pub thread_id: Option<ChannelId>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct ThreadMembersQuery {
+    pub with_member: Option<bool>,
+    pub after: Option<UserId>,
+    /// 1-100
+    pub limit: Option<u32>,
+}
+
+/// For public and private archived threads.
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct ArchivedThreadsQuery {
+    pub before: Option<iso8601_timestamp::Timestamp>,
+    pub limit: Option<u32>,
+}
+
+/// For the joined private archived threads.
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct JoinedArchivedThreadsQuery {
+    pub before: Option<ChannelId>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct GuildMembersQuery {
+    /// 1-1000, 1 by default.
+    pub limit: Option<u32>,
+    pub after: Option<UserId>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct GuildBansQuery {
+    /// 1-1000, 1000 by default.
+    pub limit: Option<u32>,
+    pub before: Option<UserId>,
+    pub after: Option<UserId>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct ReactionsQuery {
+    pub after: Option<UserId>,
+    /// 1-100, 25 by default.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct CurrentUserGuildsQuery {
+    pub before: Option<GuildId>,
+    pub after: Option<GuildId>,
+    /// 1-200, 200 by default.
+    pub limit: Option<u32>,
+    pub with_counts: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct GuildQuery {
+    pub with_counts: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug)]
+#[builder(setter(strip_option, into))]
+pub struct GuildMembersSearchQuery {
+    /// Matched against the beginning of usernames and nicknames.
+    pub query: String,
+    /// 1-1000, 1 by default.
+    #[builder(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct PruneQuery {
+    pub days: Option<u32>,
+    #[serde(serialize_with = "comma_separated", skip_serializing_if = "Vec::is_empty")]
+    #[builder(setter(each(name = "include_role")))]
+    pub include_roles: Vec<RoleId>,
+}
+
+fn comma_separated<S: serde::Serializer, T: std::fmt::Display>(items: &[T], s: S) -> Result<S::Ok, S::Error> {
+    let joined: Vec<_> = items.iter().map(ToString::to_string).collect();
+    s.serialize_str(&joined.join(","))
+}
+
+#[derive(Serialize, Clone, Builder, Debug, Default)]
+#[builder(default, setter(strip_option, into))]
+pub struct ApplicationCommandsQuery {
+    pub with_localizations: Option<bool>,
+}
@@ -1,5 +1,5 @@
 use crate::dapi::{
-    types::{dapi_endpoint, DApiGET, DApiPOST},
+    types::{dapi_endpoint, segment, DApiGET, DApiPOST},
     versions::v6,
 };
 
@@ -12,7 +12,7 @@ dapi_endpoint! {
     DApiPOST = (GiftRedeemSuccess, EntitlementRedeemBody);
 
     pub fn entitlements_giftcode_redeem(gift_code: impl AsRef<str>) {
-        format!("/entitlements/gift-codes/{}/redeem", gift_code.as_ref())
+        format!("/entitlements/gift-codes/{}/redeem", segment(gift_code.as_ref()))
     }
 }
 
@@ -21,6 +21,6 @@ dapi_endpoint! {
     DApiGET = (GiftInfo);
 
     pub fn entitlements_giftcode(gift_code: impl AsRef<str>) {
-        format!("/entitlements/gift-codes/{}", gift_code.as_ref())
+        format!("/entitlements/gift-codes/{}", segment(gift_code.as_ref()))
     }
 }
@@ -0,0 +1,177 @@
+#![allow(unused)]
+//tokens and how they're put in the Authorization header
+
+use std::{fmt, str::FromStr};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use super::routes::common_types::UserId;
+
+/// A token along with its kind. `"Bot ..."` and `"Bearer ..."` strings convert to their kinds,
+/// anything else is taken as a user token. Parsing with `str::parse` also checks the format.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Token {
+    Bot(String),
+    /// An OAuth2 access token.
+    Bearer(String),
+    User(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenError {
+    Empty,
+    /// Bot and user tokens are three base64 parts separated by dots, the first one being the user id.
+    Malformed,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the token is empty"),
+            Self::Malformed => write!(f, "the token isn't formatted like a discord token"),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+impl Token {
+    /// The token without the kind prefix, as sent in the gateway IDENTIFY.
+    pub fn secret(&self) -> &str {
+        match self {
+            Self::Bot(t) | Self::Bearer(t) | Self::User(t) => t,
+        }
+    }
+
+    /// The value of the Authorization header.
+    pub fn authorization(&self) -> String {
+        match self {
+            Self::Bot(t) => format!("Bot {t}"),
+            Self::Bearer(t) => format!("Bearer {t}"),
+            Self::User(t) => t.clone(),
+        }
+    }
+
+    pub fn is_bot(&self) -> bool {
+        matches!(self, Self::Bot(_))
+    }
+
+    /// The id of the account, which bot and user tokens start with.
+    pub fn user_id(&self) -> Option<UserId> {
+        match self {
+            Self::Bearer(_) => None,
+            Self::Bot(t) | Self::User(t) => {
+                let encoded = t.split('.').next()?.trim_end_matches('=');
+                let decoded = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+                std::str::from_utf8(&decoded).ok()?.parse().ok()
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), TokenError> {
+        let token = self.secret();
+        if token.is_empty() {
+            return Err(TokenError::Empty);
+        }
+
+        let valid = match self {
+            Self::Bearer(t) => t.bytes().all(|b| b.is_ascii_graphic()),
+            Self::Bot(t) | Self::User(t) => {
+                let parts: Vec<_> = t.split('.').collect();
+                let base64ish = |p: &str| !p.is_empty() && p.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_=".contains(&b));
+                parts.len() == 3 && parts.iter().all(|p| base64ish(p)) && self.user_id().is_some()
+            }
+        };
+        valid.then_some(()).ok_or(TokenError::Malformed)
+    }
+}
+
+impl From<&str> for Token {
+    fn from(s: &str) -> Self {
+        let s = s.trim();
+        if let Some(t) = s.strip_prefix("Bot ") {
+            Self::Bot(t.trim_start().to_owned())
+        } else if let Some(t) = s.strip_prefix("Bearer ") {
+            Self::Bearer(t.trim_start().to_owned())
+        } else {
+            Self::User(s.to_owned())
+        }
+    }
+}
+
+impl From<String> for Token {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<&String> for Token {
+    fn from(s: &String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl FromStr for Token {
+    type Err = TokenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token = Self::from(s);
+        token.validate()?;
+        Ok(token)
+    }
+}
+
+//keeps tokens out of logs
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Bot(_) => "Bot",
+            Self::Bearer(_) => "Bearer",
+            Self::User(_) => "User",
+        };
+        write!(f, "{kind}(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT: &str = "ODAzNTExMTAyMjQ2Nzg5MTI.YXhh.abc_-d";
+
+    #[test]
+    fn prefixes_pick_the_kind() {
+        assert_eq!(Token::from(format!("Bot {BOT}")), Token::Bot(BOT.to_owned()));
+        assert_eq!(Token::from("Bearer abc"), Token::Bearer("abc".to_owned()));
+        assert_eq!(Token::from(format!("  {BOT} ")), Token::User(BOT.to_owned()));
+
+        assert_eq!(Token::Bot(BOT.to_owned()).authorization(), format!("Bot {BOT}"));
+        assert_eq!(Token::Bearer("abc".to_owned()).authorization(), "Bearer abc");
+        assert_eq!(Token::User(BOT.to_owned()).authorization(), BOT);
+        assert_eq!(Token::Bot(BOT.to_owned()).secret(), BOT);
+    }
+
+    #[test]
+    fn user_ids_are_decoded_from_the_first_part() {
+        assert_eq!(Token::Bot(BOT.to_owned()).user_id(), Some(UserId::new(80351110224678912)));
+        assert_eq!(Token::User(BOT.to_owned()).user_id(), Some(UserId::new(80351110224678912)));
+        assert_eq!(Token::Bearer(BOT.to_owned()).user_id(), None);
+        assert_eq!(Token::Bot("bm90IGFuIGlk.YXhh.abc".to_owned()).user_id(), None);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(format!("Bot {BOT}").parse::<Token>().is_ok());
+        assert!("Bearer abc".parse::<Token>().is_ok());
+        assert_eq!("".parse::<Token>(), Err(TokenError::Empty));
+        assert_eq!("Bot abc.def".parse::<Token>(), Err(TokenError::Malformed));
+        assert_eq!("Bot ODAzNTExMTAyMjQ2Nzg5MTI..abc".parse::<Token>(), Err(TokenError::Malformed));
+        assert_eq!("Bot ODAzNTExMTAyMjQ2Nzg5MTI.YX hh.abc".parse::<Token>(), Err(TokenError::Malformed));
+        assert_eq!("Bearer a b".parse::<Token>(), Err(TokenError::Malformed));
+    }
+
+    #[test]
+    fn debug_output_is_redacted() {
+        assert_eq!(format!("{:?}", Token::Bot(BOT.to_owned())), "Bot(<redacted>)");
+    }
+}
@@ -0,0 +1,155 @@
+#![allow(unused)]
+//what actually sends the requests, swappable so the client can be driven without a network
+
+use std::{fmt, time::Duration};
+
+use futures_util::future::BoxFuture;
+use reqwest::{
+    header::HeaderMap,
+    multipart::{Form, Part},
+    Method, StatusCode,
+};
+
+#[derive(Clone, Debug)]
+pub struct MultipartFile {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub enum HttpBody {
+    Empty,
+    Json(Vec<u8>),
+    /// Sent as multipart/form-data: the payload_json part followed by the `files[n]` parts.
+    Multipart { payload_json: String, files: Vec<MultipartFile> },
+}
+
+impl HttpBody {
+    /// The JSON payload, for multipart bodies the payload_json part.
+    pub fn json(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::Empty => None,
+            Self::Json(b) => serde_json::from_slice(b).ok(),
+            Self::Multipart { payload_json, .. } => serde_json::from_str(payload_json).ok(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: HttpBody,
+    pub timeout: Option<Duration>,
+}
+
+/// A response read to the end.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// Couldn't connect, the request never went out.
+    Connect,
+    Timeout,
+    /// Failed while sending the request, it may or may not have reached discord.
+    Request,
+    /// Failed while reading the response.
+    Body,
+    Other,
+}
+
+#[derive(Debug)]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    pub source: Box<dyn std::error::Error + Send + Sync + 'static>,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, source: impl Into<Box<dyn std::error::Error + Send + Sync + 'static>>) -> Self {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} error: {}", self.kind, self.source)
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        let kind = if e.is_connect() {
+            TransportErrorKind::Connect
+        } else if e.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if e.is_request() {
+            TransportErrorKind::Request
+        } else if e.is_body() || e.is_decode() {
+            TransportErrorKind::Body
+        } else {
+            TransportErrorKind::Other
+        };
+        Self::new(kind, e)
+    }
+}
+
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>>;
+}
+
+/// The default transport.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, req: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            let mut builder = self.client.request(req.method, req.url).headers(req.headers);
+            if let Some(timeout) = req.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            builder = match req.body {
+                HttpBody::Empty => builder,
+                HttpBody::Json(b) => builder.body(b),
+                HttpBody::Multipart { payload_json, files } => {
+                    let mut form = Form::new().text("payload_json", payload_json);
+                    for (i, f) in files.into_iter().enumerate() {
+                        form = form.part(format!("files[{i}]"), Part::bytes(f.data).file_name(f.filename));
+                    }
+                    builder.multipart(form)
+                }
+            };
+
+            let resp = builder.send().await?;
+            Ok(HttpResponse {
+                status: resp.status(),
+                headers: resp.headers().clone(),
+                body: resp.bytes().await?.to_vec(),
+            })
+        })
+    }
+}
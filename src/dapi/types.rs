@@ -1,4 +1,7 @@
-use super::routes::common_types::DiscordApiError;
+use std::time::Duration;
+
+use super::{cdn::CdnError, ratelimit::RateLimitExceeded, routes::common_types::DiscordApiError, transport::TransportError};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::{de::DeserializeOwned, Serialize};
 
 pub type Result<T> = std::result::Result<T, DApiError>;
@@ -6,10 +9,80 @@ pub type Result<T> = std::result::Result<T, DApiError>;
 #[derive(Debug)]
 pub enum DApiError {
     Instantiation(reqwest::Error),
-    Requesting(reqwest::Error),
+    Requesting(TransportError),
     ParsingResponse(Box<dyn std::error::Error + Send + Sync + 'static>),
     ApiError(DiscordApiError),
-    ApiErrorWithoutBody(reqwest::Error),
+    ApiErrorWithoutBody(reqwest::StatusCode),
+    SerializingBody(serde_json::Error),
+    ReadingFile(std::io::Error),
+    InvalidCdnUrl(CdnError),
+    RetriesExhausted {
+        attempts: u32,
+        source: Box<DApiError>,
+    },
+    RateLimited {
+        retry_after: Duration,
+        scope: Option<String>,
+        bucket: Option<String>,
+        global: bool,
+    },
+    /// Every error of a request comes wrapped in this one, saying which request it was.
+    Request {
+        context: Box<RequestContext>,
+        source: Box<DApiError>,
+    },
+}
+
+/// Which request failed, and how the last response to it looked if there was one.
+#[derive(Clone, Debug)]
+pub struct RequestContext {
+    pub method: reqwest::Method,
+    /// The path with ids and tokens left out, see `ratelimit::Route`.
+    pub route: String,
+    pub status: Option<reqwest::StatusCode>,
+    pub request_id: Option<String>,
+    /// How many attempts were made, 429s that were waited out aside.
+    pub attempts: u32,
+}
+
+impl DApiError {
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::Request { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error underneath the request context and the retries.
+    pub fn root(&self) -> &DApiError {
+        match self {
+            Self::Request { source, .. } | Self::RetriesExhausted { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// The error discord responded with, if that's what this is.
+    pub fn api_error(&self) -> Option<&DiscordApiError> {
+        match self.root() {
+            Self::ApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        self.context().and_then(|c| c.status)
+    }
+}
+
+impl From<RateLimitExceeded> for DApiError {
+    fn from(e: RateLimitExceeded) -> Self {
+        Self::RateLimited {
+            retry_after: e.retry_after,
+            scope: e.scope,
+            bucket: e.bucket,
+            global: e.global,
+        }
+    }
 }
 
 impl std::fmt::Display for DApiError {
@@ -23,8 +96,41 @@ impl std::fmt::Display for DApiError {
                 f,
                 "An error has occured while trying to parse the http response: {e}"
             ),
-            Self::ApiError(e) => write!(f, "Discord API Error: {:#?}", e),
-            Self::ApiErrorWithoutBody(e) => write!(f, "Http error: {e}"),
+            Self::ApiError(e) => {
+                write!(f, "Discord API Error {} ({:?}): {}", u32::from(e.code), e.code, e.message)?;
+                for fe in e.field_errors() {
+                    write!(f, "\n  {}: {} ({})", if fe.path.is_empty() { "<body>" } else { &fe.path }, fe.message, fe.code)?;
+                }
+                Ok(())
+            }
+            Self::ApiErrorWithoutBody(status) => write!(f, "Http error: {status}"),
+            Self::SerializingBody(e) => write!(f, "Couldn't serialize the request body: {e}"),
+            Self::ReadingFile(e) => write!(f, "Couldn't read a file to upload: {e}"),
+            Self::InvalidCdnUrl(e) => write!(f, "Invalid cdn url: {e}"),
+            Self::RetriesExhausted { attempts, source } => {
+                write!(f, "Giving up after {attempts} attempts, last error: {source}")
+            }
+            Self::RateLimited { retry_after, scope, global, .. } => write!(
+                f,
+                "Rate limited{} for {:.3}s{}",
+                if *global { " globally" } else { "" },
+                retry_after.as_secs_f64(),
+                scope.as_ref().map(|s| format!(" (scope: {s})")).unwrap_or_default()
+            ),
+            Self::Request { context, source } => {
+                write!(f, "{} {}", context.method, context.route)?;
+                if let Some(status) = context.status {
+                    write!(f, " ({status})")?;
+                }
+                if let Some(ref id) = context.request_id {
+                    write!(f, " [request {id}]")?;
+                }
+                //RetriesExhausted says so itself
+                if context.attempts > 1 && !matches!(**source, Self::RetriesExhausted { .. }) {
+                    write!(f, " on attempt {}", context.attempts)?;
+                }
+                write!(f, ": {source}")
+            }
         }
     }
 }
@@ -32,9 +138,14 @@ impl std::fmt::Display for DApiError {
 impl std::error::Error for DApiError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::Instantiation(e) | Self::Requesting(e) | Self::ApiErrorWithoutBody(e) => Some(e),
+            Self::Instantiation(e) => Some(e),
+            Self::Requesting(e) => Some(e),
             Self::ParsingResponse(e) => Some(&**e),
-            Self::ApiError(_) => None,
+            Self::SerializingBody(e) => Some(e),
+            Self::ReadingFile(e) => Some(e),
+            Self::InvalidCdnUrl(e) => Some(e),
+            Self::ApiError(_) | Self::ApiErrorWithoutBody(_) | Self::RateLimited { .. } => None,
+            Self::RetriesExhausted { source, .. } | Self::Request { source, .. } => Some(&**source),
         }
     }
 }
@@ -67,10 +178,32 @@ pub trait DApiDELETE<V: DApiVersion>: DApiMethod<V> {
     type Response: DeserializeOwned;
 }
 
+//everything but the unreserved characters of RFC 3986
+pub(crate) const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// A string percent-encoded to fit in a single path segment.
+pub(crate) fn segment(s: &str) -> impl std::fmt::Display + '_ {
+    utf8_percent_encode(s, URL_COMPONENT)
+}
+
+/// Appends the query string made from `query`, unset (`None`) fields are left out.
+pub(crate) fn with_query(path: impl AsRef<str>, query: &impl Serialize) -> String {
+    let query = serde_urlencoded::to_string(query).expect("query structs serialize to flat key-value pairs");
+    let path = path.as_ref();
+    if query.is_empty() {
+        path.to_owned()
+    } else {
+        //raw paths may come with a query of their own
+        let sep = if path.contains('?') { '&' } else { '?' };
+        format!("{path}{sep}{query}")
+    }
+}
+
+//the path is the body of the function, optionally followed by `? query` where query is a Serialize struct
 macro_rules! dapi_endpoint {
     (   version = $ver:ty
         $(, $meth:tt = ($resp:ty $(, $body:ty)?))+ ;
-        $vis:vis fn $name:ident $args:tt $rest:block
+        $vis:vis fn $name:ident $args:tt $rest:block $(? $query:expr)?
     ) => {
         $vis fn $name $args -> impl $($meth<$ver, Response = $resp, $(Body = $body)?> + )+ {
             struct T<E>(E);
@@ -86,7 +219,9 @@ macro_rules! dapi_endpoint {
                 }
             )+
 
-            return T($rest);
+            let path = $rest;
+            $(let path = crate::dapi::types::with_query(path, &$query);)?
+            return T(path);
         }
     };
 }
@@ -65,7 +65,7 @@ impl<'a> Display for GCError<'a> {
             ConnectError(we) => write!(f, "Connecting with the remote websocket failed: {}", we),
             WSInternal(we) => write!(f, "Unexpected WS error: {}", we),
             NoHeartbeat => write!(f, "Didn't receive a Heartbeat ACK in time"),
-            Misc(Some(e), desc) => write!(f, "{}", format!("{}: {}", desc, e)),
+            Misc(Some(e), desc) => write!(f, "{}: {}", desc, e),
             Misc(None, desc) => write!(f, "{}", desc),
         }
     }
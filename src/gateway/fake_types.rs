@@ -2,14 +2,16 @@
 //incomplete or untruthful gateway type definitions, useful only for danielek purposes
 
 use derive_builder::Builder;
+use log::warn;
 use serde::{Serialize, Deserialize};
 use smartstring::alias::String;
 
-use crate::dapi::routes::{v10::types::{Message, User, Channel, Guild, GuildMember}, common_types::Snowflake};
+use crate::dapi::routes::{v10::types::{Message, User, Channel, ChannelType, Guild, GuildMember, Role, Emoji, VoiceState, ThreadMember, Interaction}, common_types::{ChannelId, GuildId, RoleId, UserId}};
 
 use super::types::{GatewayOpcode, GatewayIntents, GatewayPresenceSend};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum GatewayDispatchEventName {
     READY,
     CHANNEL_CREATE,
@@ -20,6 +22,22 @@ pub enum GatewayDispatchEventName {
     GUILD_DELETE,
     MESSAGE_CREATE,
     MESSAGE_UPDATE,
+    GUILD_MEMBER_ADD,
+    GUILD_MEMBER_UPDATE,
+    GUILD_MEMBER_REMOVE,
+    GUILD_MEMBERS_CHUNK,
+    GUILD_ROLE_CREATE,
+    GUILD_ROLE_UPDATE,
+    GUILD_ROLE_DELETE,
+    GUILD_EMOJIS_UPDATE,
+    VOICE_STATE_UPDATE,
+    THREAD_CREATE,
+    THREAD_UPDATE,
+    THREAD_DELETE,
+    THREAD_LIST_SYNC,
+    THREAD_MEMBER_UPDATE,
+    THREAD_MEMBERS_UPDATE,
+    INTERACTION_CREATE,
     #[serde(other)]
     Other
 }
@@ -62,6 +80,18 @@ impl<'de> Deserialize<'de> for GatewayEvent { //to avoid #[serde(untagged)], unt
         macro_rules! inner {
             () => { serde_json::from_str(d_as_str?).map_err(serde::de::Error::custom)? };
         }
+        //a dispatch we can't parse is passed on without its data instead of ending the stream, its sequence number still counts
+        macro_rules! dispatch {
+            ($variant:ident) => {
+                match serde_json::from_str(d_as_str?) {
+                    Ok(d) => Some(GD::$variant(d)),
+                    Err(e) => {
+                        warn!("Could not deserialize the {:?} dispatch, skipping its data: {e}", ev.t);
+                        None
+                    }
+                }
+            };
+        }
         use {GatewayOpcode as OP, GatewayData as GD, GatewayDispatchEventName as GE};
 
         let d = match (ev.op, ev.t) {
@@ -72,14 +102,30 @@ impl<'de> Deserialize<'de> for GatewayEvent { //to avoid #[serde(untagged)], unt
             (OP::INVALID_SESSION, _) =>                 Some(GD::InvalidSession(inner!())),
             (OP::HELLO, _) =>                           Some(GD::Hello(inner!())),
             (OP::DISPATCH, Some(GE::READY)) =>          Some(GD::Ready(inner!())),
-            (OP::DISPATCH, Some(GE::CHANNEL_CREATE)) => Some(GD::ChannelCreate(inner!())),
-            (OP::DISPATCH, Some(GE::CHANNEL_UPDATE)) => Some(GD::ChannelUpdate(inner!())),
-            (OP::DISPATCH, Some(GE::CHANNEL_DELETE)) => Some(GD::ChannelDelete(inner!())),
-            (OP::DISPATCH, Some(GE::GUILD_CREATE)) =>   Some(GD::GuildCreate(inner!())),
-            (OP::DISPATCH, Some(GE::GUILD_UPDATE)) =>   Some(GD::GuildUpdate(inner!())),
-            (OP::DISPATCH, Some(GE::GUILD_DELETE)) =>   Some(GD::GuildDelete(inner!())),
-            (OP::DISPATCH, Some(GE::MESSAGE_CREATE)) => Some(GD::MessageCreate(inner!())),
-            (OP::DISPATCH, Some(GE::MESSAGE_UPDATE)) => Some(GD::MessageUpdate(inner!())),
+            (OP::DISPATCH, Some(GE::CHANNEL_CREATE)) => dispatch!(ChannelCreate),
+            (OP::DISPATCH, Some(GE::CHANNEL_UPDATE)) => dispatch!(ChannelUpdate),
+            (OP::DISPATCH, Some(GE::CHANNEL_DELETE)) => dispatch!(ChannelDelete),
+            (OP::DISPATCH, Some(GE::GUILD_CREATE)) =>   dispatch!(GuildCreate),
+            (OP::DISPATCH, Some(GE::GUILD_UPDATE)) =>   dispatch!(GuildUpdate),
+            (OP::DISPATCH, Some(GE::GUILD_DELETE)) =>   dispatch!(GuildDelete),
+            (OP::DISPATCH, Some(GE::MESSAGE_CREATE)) => dispatch!(MessageCreate),
+            (OP::DISPATCH, Some(GE::MESSAGE_UPDATE)) => dispatch!(MessageUpdate),
+            (OP::DISPATCH, Some(GE::GUILD_MEMBER_ADD)) =>       dispatch!(GuildMemberAdd),
+            (OP::DISPATCH, Some(GE::GUILD_MEMBER_UPDATE)) =>    dispatch!(GuildMemberUpdate),
+            (OP::DISPATCH, Some(GE::GUILD_MEMBER_REMOVE)) =>    dispatch!(GuildMemberRemove),
+            (OP::DISPATCH, Some(GE::GUILD_MEMBERS_CHUNK)) =>    dispatch!(GuildMembersChunk),
+            (OP::DISPATCH, Some(GE::GUILD_ROLE_CREATE)) =>      dispatch!(GuildRoleCreate),
+            (OP::DISPATCH, Some(GE::GUILD_ROLE_UPDATE)) =>      dispatch!(GuildRoleUpdate),
+            (OP::DISPATCH, Some(GE::GUILD_ROLE_DELETE)) =>      dispatch!(GuildRoleDelete),
+            (OP::DISPATCH, Some(GE::GUILD_EMOJIS_UPDATE)) =>    dispatch!(GuildEmojisUpdate),
+            (OP::DISPATCH, Some(GE::VOICE_STATE_UPDATE)) =>     dispatch!(VoiceStateUpdate),
+            (OP::DISPATCH, Some(GE::THREAD_CREATE)) =>          dispatch!(ThreadCreate),
+            (OP::DISPATCH, Some(GE::THREAD_UPDATE)) =>          dispatch!(ThreadUpdate),
+            (OP::DISPATCH, Some(GE::THREAD_DELETE)) =>          dispatch!(ThreadDelete),
+            (OP::DISPATCH, Some(GE::THREAD_LIST_SYNC)) =>       dispatch!(ThreadListSync),
+            (OP::DISPATCH, Some(GE::THREAD_MEMBER_UPDATE)) =>   dispatch!(ThreadMemberUpdate),
+            (OP::DISPATCH, Some(GE::THREAD_MEMBERS_UPDATE)) =>  dispatch!(ThreadMembersUpdate),
+            (OP::DISPATCH, Some(GE::INTERACTION_CREATE)) =>     dispatch!(InteractionCreate),
             _ => None
         };
 
@@ -114,7 +160,23 @@ pub enum GatewayData {
     GuildUpdate(Box<Guild>),
     GuildDelete(UnavailableGuild),
     MessageCreate(Box<MessageExtra>),
-    MessageUpdate(Box<MessageExtra>)
+    MessageUpdate(Box<MessageExtra>),
+    GuildMemberAdd(Box<GatewayGuildMemberAddPayload>),
+    GuildMemberUpdate(Box<GatewayGuildMemberUpdatePayload>),
+    GuildMemberRemove(Box<GatewayGuildMemberRemovePayload>),
+    GuildMembersChunk(Box<GatewayGuildMembersChunkPayload>),
+    GuildRoleCreate(Box<GatewayGuildRolePayload>),
+    GuildRoleUpdate(Box<GatewayGuildRolePayload>),
+    GuildRoleDelete(GatewayGuildRoleDeletePayload),
+    GuildEmojisUpdate(Box<GatewayGuildEmojisUpdatePayload>),
+    VoiceStateUpdate(Box<VoiceState>),
+    ThreadCreate(Box<GatewayThreadCreatePayload>),
+    ThreadUpdate(Box<Channel>),
+    ThreadDelete(GatewayThreadDeletePayload),
+    ThreadListSync(Box<GatewayThreadListSyncPayload>),
+    ThreadMemberUpdate(Box<GatewayThreadMemberUpdatePayload>),
+    ThreadMembersUpdate(Box<GatewayThreadMembersUpdatePayload>),
+    InteractionCreate(Box<Interaction>)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -158,9 +220,13 @@ pub struct GatewayGuild {
     pub large: bool,
     pub unavailable: Option<bool>,
     pub member_count: i64,
-    //pub voice_states: partial voice states... idc,
-    //pub members: Vec<GuildMember>,
+    #[serde(default)]
+    pub voice_states: Vec<VoiceState>, //partial, without guild_id
+    #[serde(default)]
+    pub members: Vec<GuildMember>,
+    #[serde(default)]
     pub channels: Vec<Channel>,
+    #[serde(default)]
     pub threads: Vec<Channel>,
     //pub presences: Vec<GatewayPresence>, this bitch can be partial
     //pub stage_instances: Vec<StageInstance> tf is a stage,
@@ -171,12 +237,13 @@ pub struct GatewayGuild {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct UnavailableGuild {
-    pub id: Snowflake,
+    pub id: GuildId,
     pub unavailable: Option<bool>
 }
 
 #[derive(Serialize, Clone, Debug)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum GatewayGuildCreatePayload {
     Unavailable(UnavailableGuild),
     Available(GatewayGuild),
@@ -206,9 +273,138 @@ pub struct GatewayReadyPayload {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MessageExtra {
-    pub guild_id: Option<Snowflake>,
+    pub guild_id: Option<GuildId>,
     pub member: Option<GuildMember>,
     //pub mentions array of user objects, with an additional partial member field
     #[serde(flatten)]
     pub rest: Message
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildMemberAddPayload {
+    pub guild_id: GuildId,
+    #[serde(flatten)]
+    pub member: GuildMember
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildMemberUpdatePayload { //like GuildMember, but anything can be missing
+    pub guild_id: GuildId,
+    pub roles: Vec<RoleId>,
+    pub user: User,
+    pub nick: Option<String>,
+    pub avatar: Option<String>,
+    pub joined_at: Option<iso8601_timestamp::Timestamp>,
+    pub premium_since: Option<iso8601_timestamp::Timestamp>,
+    pub deaf: Option<bool>,
+    pub mute: Option<bool>,
+    pub pending: Option<bool>,
+    pub communication_disabled_until: Option<iso8601_timestamp::Timestamp>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildMemberRemovePayload {
+    pub guild_id: GuildId,
+    pub user: User
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildMembersChunkPayload {
+    pub guild_id: GuildId,
+    pub members: Vec<GuildMember>,
+    pub chunk_index: i32,
+    pub chunk_count: i32,
+    pub not_found: Option<Vec<UserId>>,
+    //pub presences: Vec<GatewayPresence>, partial again
+    pub nonce: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildRolePayload {
+    pub guild_id: GuildId,
+    pub role: Role
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildRoleDeletePayload {
+    pub guild_id: GuildId,
+    pub role_id: RoleId
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayGuildEmojisUpdatePayload {
+    pub guild_id: GuildId,
+    pub emojis: Vec<Emoji>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayThreadCreatePayload {
+    #[serde(default)]
+    pub newly_created: bool, //false when the current user was just added to an existing thread
+    #[serde(flatten)]
+    pub thread: Channel
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct GatewayThreadDeletePayload {
+    pub id: ChannelId,
+    pub guild_id: GuildId,
+    pub parent_id: Option<ChannelId>,
+    pub r#type: ChannelType
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayThreadListSyncPayload {
+    pub guild_id: GuildId,
+    pub channel_ids: Option<Vec<ChannelId>>, //parents being synced, the whole guild if missing
+    pub threads: Vec<Channel>,
+    pub members: Vec<ThreadMember> //only the current user's
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayThreadMemberUpdatePayload {
+    pub guild_id: GuildId,
+    #[serde(flatten)]
+    pub member: ThreadMember
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GatewayThreadMembersUpdatePayload {
+    pub id: ChannelId,
+    pub guild_id: GuildId,
+    pub member_count: i32,
+    pub added_members: Option<Vec<ThreadMember>>,
+    pub removed_member_ids: Option<Vec<UserId>>
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn unparseable_dispatches_keep_their_sequence() {
+        let event: GatewayEvent = serde_json::from_str(&json!({
+            "op": 0,
+            "s": 42,
+            "t": "GUILD_MEMBER_ADD",
+            "d": { "guild_id": "1", "roles": "not a list" },
+        }).to_string())
+        .unwrap();
+        assert_eq!(event.s, Some(42));
+        assert!(event.d.is_none());
+    }
+
+    #[test]
+    fn guild_creates_without_lists_parse() {
+        let event: GatewayEvent = serde_json::from_str(&json!({
+            "op": 0,
+            "s": 1,
+            "t": "GUILD_CREATE",
+            "d": { "id": "1", "unavailable": true },
+        }).to_string())
+        .unwrap();
+        assert!(matches!(event.d, Some(GatewayData::GuildCreate(ref g)) if matches!(**g, GatewayGuildCreatePayload::Unavailable(_))));
+    }
+}
@@ -11,6 +11,7 @@ use tokio::{
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_tungstenite::{connect_async_with_config, tungstenite::protocol::WebSocketConfig};
 
+use crate::dapi::token::Token;
 use crate::gateway::{error::GCError, util::try_x_times};
 
 use super::{
@@ -28,11 +29,17 @@ pub struct GatewayShard {
 }
 
 impl GatewayShard {
+    /// The gateway gets the token without its kind prefix, bearer tokens can't connect at all.
     pub async fn new(
-        token: impl Into<String>,
+        token: impl Into<Token>,
         intents: GatewayIntents,
         force_reconnect: bool,
     ) -> GCResult<GatewayShard> {
+        let token = token.into();
+        if let Token::Bearer(_) = token {
+            return Err(GCError::Misc(None, "Bearer tokens can't identify with the gateway".into()));
+        }
+
         let ws_config = WebSocketConfig {
             max_send_queue: None,
             max_message_size: Some(1 << 30),
@@ -57,7 +64,7 @@ impl GatewayShard {
             last_ack: Instant::now(),
             heartbeat_interval: Duration::from_secs(3600),
             last_sequence: 0,
-            token: token.into().into(),
+            token: token.secret().into(),
             intents,
             resume_info: None,
             websocket_config: ws_config,
@@ -101,11 +108,8 @@ impl GatewayShard {
                 }
 
                 if conn.force_reconnect {
-                    loop {
-                        match try_x_times!(20, conn.reconnect().await) {
-                            Err(why) => error!("Force reconnect keeps failing with: {why}"),
-                            Ok(_) => break
-                        }
+                    while let Err(why) = try_x_times!(20, conn.reconnect().await) {
+                        error!("Force reconnect keeps failing with: {why}");
                     }
                 } else {
                     conn.evnt_tx.send(Err(err)).ok();
@@ -4,7 +4,7 @@ use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String;
 
-use crate::dapi::routes::common_types::Snowflake;
+use crate::dapi::routes::common_types::{ApplicationId, EmojiId};
 
 bitflags::bitflags! {
     #[derive(Serialize, Deserialize)]
@@ -96,7 +96,7 @@ pub struct GatewayActivityTimestamps {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GatewayActivityEmoji {
     pub name: String,
-    pub id: Option<Snowflake>,
+    pub id: Option<EmojiId>,
     pub animated: Option<bool>,
 }
 
@@ -158,7 +158,7 @@ pub struct GatewayActivity {
     #[builder(default)]
     pub timestamps: Option<GatewayActivityTimestamps>,
     #[builder(default)]
-    pub application_id: Option<Snowflake>,
+    pub application_id: Option<ApplicationId>,
     #[builder(default)]
     pub details: Option<String>,
     #[builder(default)]
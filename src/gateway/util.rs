@@ -44,5 +44,5 @@ pub async fn fetch_wss_url() -> GCResult<String> {
         ))
     }
     .await
-    .map_err(|e| GCError::GatewayURLFetch(e))
+    .map_err(GCError::GatewayURLFetch)
 }
@@ -1,10 +1,12 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use futures_util::future::select_all;
 
+use dapi::{versions::v10, DApi};
 use scanner::{message_relay::MessageRelay, GiftScanner};
 use simplelog::{CombinedLogger, TermLogger, WriteLogger, ConfigBuilder};
 
+mod cache;
 mod dapi;
 mod gateway;
 mod scanner;
@@ -45,17 +47,29 @@ async fn main() {
     let (cmd_guild, cmd_channel) = vars[3]
         .split_once('/')
         .expect("Invalid COMMAND_GUILD_CHANNEL format (should be \"id/id\")");
-    let relay = Arc::new(MessageRelay::new(webhook_id, webhook_token).unwrap());
+    let webhook_id = webhook_id.parse().expect("Invalid WEBHOOK id");
+    let (cmd_guild, cmd_channel) = (
+        cmd_guild.parse().expect("Invalid COMMAND_GUILD_CHANNEL guild id"),
+        cmd_channel.parse().expect("Invalid COMMAND_GUILD_CHANNEL channel id"),
+    );
+    let dapi = DApi::<v10>::new().unwrap();
+    let relay = Arc::new(MessageRelay::new(dapi.clone(), webhook_id, webhook_token));
+    let snapshot_dir = std::env::var("SNAPSHOT_DIR").ok().map(PathBuf::from);
+    if let Some(dir) = &snapshot_dir {
+        std::fs::create_dir_all(dir).expect("Could not create SNAPSHOT_DIR");
+    }
 
     let mut tasks = vec![];
     for token in vars[0].split(',') {
         let mut scanner = GiftScanner::new(
+            &dapi,
             token,
             &vars[1],
             false,
             cmd_channel,
             cmd_guild,
             Arc::clone(&relay),
+            snapshot_dir.clone(),
         )
         .await
         .unwrap();
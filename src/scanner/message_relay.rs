@@ -7,12 +7,13 @@ use crate::dapi::{
     routes::v10::{
         types::{
             EmbedAuthorBuilder, EmbedBuilder, EmbedField, Message, MessagePayload,
-            MessagePayloadBuilder,
+            MessagePayloadBuilder, WebhookExecuteQuery,
         },
         webhook_execute,
     },
+    routes::common_types::WebhookId,
     versions::v10,
-    DApi, DApiError, DApiPOST,
+    DApi, DApiPOST,
 };
 
 pub enum GiftRedeemAttempt<'a> {
@@ -49,20 +50,21 @@ impl MessageRelay {
     const INFO_COLOR: i32 = 0x00b3fa;
     const ERROR_COLOR: i32 = 0xff1a1a;
 
+    /// `dapi` only needs to share the transport, webhooks don't use its token.
     pub fn new(
-        webhook_id: impl Into<String>,
+        dapi: DApi<v10>,
+        webhook_id: WebhookId,
         webhook_token: impl Into<String>,
-    ) -> Result<Self, DApiError> {
-        Ok(Self {
-            dapi: DApi::new()?,
+    ) -> Self {
+        Self {
+            dapi,
             fcfs: Mutex::new(()),
             route: Box::new(webhook_execute(
-                webhook_id.into(),
+                webhook_id,
                 webhook_token.into(),
-                true,
-                None::<&str>,
+                WebhookExecuteQuery { wait: Some(true), ..Default::default() },
             )),
-        })
+        }
     }
 
     async fn send(&self, msg: &MessagePayload) {
@@ -198,6 +200,7 @@ impl MessageRelay {
         .await;
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn command_stats(
         &self,
         scanner: &str,
@@ -1,7 +1,8 @@
-use crate::dapi::routes::common_types::Snowflake;
+use crate::cache::{Cache, CacheConfigBuilder, CacheResources, CacheSnapshot};
+use crate::dapi::routes::common_types::{ChannelId, GuildId, JsonErrorCode, UserId};
 use crate::dapi::routes::{v10 as v10Routes, v6 as v6Routes};
 use crate::dapi::versions::{v10, v6};
-use crate::dapi::{DApi, DApiError};
+use crate::dapi::{token::Token, DApi};
 use crate::gateway::error::GCResult;
 use crate::gateway::fake_types::{GatewayData, GatewayEvent, MessageExtra, UnavailableGuild, GatewayGuildCreatePayload, GatewayReadyPayload};
 use crate::gateway::shard::GatewayShard;
@@ -11,13 +12,14 @@ use crate::gateway::types::{
 };
 use futures_util::StreamExt;
 use lazy_regex::regex;
-use log::info;
+use log::{info, warn};
 use once_cell::sync::Lazy;
-use tokio::sync::oneshot;
+use tokio::{sync::oneshot, time::MissedTickBehavior};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 use smartstring::alias::String;
 
@@ -29,7 +31,7 @@ type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync
 
 #[derive(Default, Debug)]
 struct SharedData {
-    guilds: Mutex<HashMap<uuid::Uuid, HashSet<String>>>,
+    guilds: Mutex<HashMap<uuid::Uuid, HashSet<GuildId>>>,
     used_codes: Mutex<HashSet<String>>,
 }
 
@@ -43,12 +45,13 @@ pub struct GiftScanner {
     username: String,
     shard: GatewayShard,
     ignore: bool,
-    command_channel: Snowflake,
-    command_guild: Snowflake,
+    command_channel: ChannelId,
+    command_guild: GuildId,
     ready_at: Option<Instant>,
     last_msg: Option<Box<MessageExtra>>,
-    guild_names: HashMap<Snowflake, String>,
-    channel_names: HashMap<Snowflake, String>,
+    cache: Cache,
+    snapshot_dir: Option<PathBuf>,
+    snapshot_dirty: bool,
     ready_event: Option<oneshot::Sender<()>>
 }
 
@@ -59,13 +62,19 @@ impl Drop for GiftScanner {
 }
 
 impl GiftScanner {
+    const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Requests go through clones of `dapi`, keeping one transport for every scanner.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
-        token: impl Into<String>,
-        redeem_token: impl Into<String>,
+        dapi: &DApi<v10>,
+        token: impl Into<Token>,
+        redeem_token: impl Into<Token>,
         ignore: bool,
-        command_channel: impl Into<String>,
-        command_guild: impl Into<String>,
+        command_channel: ChannelId,
+        command_guild: GuildId,
         relay: Arc<MessageRelay>,
+        snapshot_dir: Option<PathBuf>,
     ) -> Result<Self> {
         let intents = GatewayIntents::GUILDS
             | GatewayIntents::MESSAGE_CONTENT
@@ -73,31 +82,50 @@ impl GiftScanner {
             | GatewayIntents::DIRECT_MESSAGES;
 
         let token = token.into();
+        let user_id = token.user_id();
         let shard = GatewayShard::new(token.clone(), intents, true).await?;
 
         let id = Uuid::new_v4();
         SHARED.guilds.lock().unwrap().insert(id, Default::default());
 
         let mut this = Self {
-            dapi: DApi::new()?,
-            redeem_dapi: DApi::new()?,
+            dapi: dapi.clone(),
+            redeem_dapi: dapi.version(),
             relay,
             id,
             username: String::new(),
             shard,
             ignore,
-            command_channel: command_channel.into(),
-            command_guild: command_guild.into(),
+            command_channel,
+            command_guild,
             ready_at: None,
             last_msg: None,
-            guild_names: HashMap::new(),
-            channel_names: HashMap::new(),
+            cache: Cache::new(CacheConfigBuilder::default()
+                .resources(CacheResources::GUILDS | CacheResources::CHANNELS)
+                .build()?),
+            snapshot_dir: None,
+            snapshot_dirty: false,
             ready_event: None
         };
 
         this.dapi.set_token(token);
         this.redeem_dapi.set_token(redeem_token.into());
 
+        //snapshots are per account, the id in the token picks the right one before READY
+        match (&snapshot_dir, user_id) {
+            (Some(dir), Some(user_id)) => {
+                let path = Self::snapshot_path(dir, user_id);
+                match CacheSnapshot::load(&path).await {
+                    Ok(snapshot) => this.cache.restore(snapshot),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+                    Err(e) => warn!("{user_id}: Could not load cache snapshot {}: {e}", path.display()),
+                }
+            }
+            (Some(_), None) => warn!("Token doesn't carry a user id, starting without a cache snapshot"),
+            _ => ()
+        }
+        this.snapshot_dir = snapshot_dir;
+
         Ok(this)
     }
 
@@ -108,24 +136,48 @@ impl GiftScanner {
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        let res = self.run().await;
+        //whatever stopped the scanner, the latest state is kept
+        self.save_snapshot().await;
+        res
+    }
+
+    async fn run(&mut self) -> Result<()> {
         let mut recv = self
             .shard
             .get_event_stream()
             .ok_or("Cannot get gateway event stream")?;
-        while let Some(e) = recv.next().await {
+        let mut flush = tokio::time::interval(Self::SNAPSHOT_INTERVAL);
+        flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let e = tokio::select! {
+                e = recv.next() => match e {
+                    Some(e) => e,
+                    None => break
+                },
+                _ = flush.tick() => {
+                    self.save_snapshot().await;
+                    continue;
+                }
+            };
+
             match e {
                 Ok(e) => {
                     if let Some(data) = e.d {
+                        self.cache.update(&data);
+
                         use GatewayData::*;
+                        if matches!(data, Ready(_) | GuildCreate(_) | GuildUpdate(_) | GuildDelete(_)
+                            | ChannelCreate(_) | ChannelUpdate(_) | ChannelDelete(_)) {
+                            self.snapshot_dirty = true;
+                        }
+
                         match data {
                             Ready(r) => self.handle_ready(r).await?,
                             GuildCreate(g) => self.handle_guild_create(&g).await,
                             GuildDelete(g) => self.handle_guild_delete(&g).await,
-                            GuildUpdate(g) => { self.guild_names.insert(g.id, g.name); }
                             MessageCreate(m) | MessageUpdate(m) => self.handle_message_create(m).await,
-                            ChannelCreate(c) | ChannelUpdate(c) if c.name.is_some()
-                                => { self.channel_names.insert(c.id, c.name.unwrap()); }
-                            ChannelDelete(c) => { self.channel_names.remove(&c.id); }
                             _ => continue
                         };
                     }
@@ -182,18 +234,14 @@ impl GiftScanner {
             }
         };
 
-        let channel_name = self.channel_names.get(&msg.rest.channel_id)
-            .map(|s| s.as_str())
-            .unwrap_or("??");
-        let guild_name = msg.guild_id.as_ref()
-            .and_then(|g| self.guild_names.get(g).map(|s| s.as_str()))
-            .unwrap_or("??");
+        let channel_name = self.channel_name(msg.rest.channel_id);
+        let guild_name = self.guild_name(msg.guild_id);
         let safe_content = regex!("(?:@everyone)|(?:@here)").replace_all(content, "");
 
         let mut report = GiftReport {
             from: msg.rest.author.as_ref().map(|u| u.username.as_str()).unwrap_or("??").into(),
-            channel: channel_name.into(),
-            guild: guild_name.into(),
+            channel: channel_name.to_string().into(),
+            guild: guild_name.to_string().into(),
             ping: self.shard.get_ping(),
             content: safe_content,
             attempts: vec![],
@@ -213,23 +261,48 @@ impl GiftScanner {
         self.relay.gift_report(&self.username, report).await;
     }
 
-    async fn redeem_code<'a>(&self, code: Cow<'a, str>) -> Result<(Cow<'a, str>, GiftRedeemAttempt)> {
+    fn snapshot_path(dir: &Path, user_id: UserId) -> PathBuf {
+        dir.join(format!("{user_id}.json"))
+    }
+
+    async fn save_snapshot(&mut self) {
+        //named after the account READY reported, so it is only ever restored for that account
+        let (Some(dir), Some(user)) = (self.snapshot_dir.as_ref(), self.cache.current_user()) else { return };
+        if !self.snapshot_dirty {
+            return;
+        }
+
+        let path = Self::snapshot_path(dir, user.id);
+        if let Err(e) = self.cache.snapshot().save(&path).await {
+            warn!("@{}: Could not save cache snapshot {}: {e}", self.username, path.display());
+        }
+        self.snapshot_dirty = false;
+    }
+
+    fn channel_name(&self, id: ChannelId) -> String {
+        self.cache.channel(id)
+            .and_then(|c| c.name)
+            .unwrap_or_else(|| "??".into())
+    }
+
+    fn guild_name(&self, id: Option<GuildId>) -> String {
+        id.and_then(|id| self.cache.with_guild(id, |g| g.guild.name.clone()))
+            .unwrap_or_else(|| "??".into())
+    }
+
+    async fn redeem_code<'a>(&self, code: Cow<'a, str>) -> Result<(Cow<'a, str>, GiftRedeemAttempt<'_>)> {
         if self.ignore {
             let res = self
                 .redeem_dapi
                 .get(&v6Routes::entitlements_giftcode(&code))
                 .await;
             match res {
-                Err(DApiError::ApiError(e)) => {
-                    if e.code == 10038 {
+                Err(err) => match err.api_error() {
+                    Some(e) if e.code == JsonErrorCode::UnknownGiftCode => {
                         return Ok((code, GiftRedeemAttempt::Invalid { info: e.to_string().into() }));
-                    } else {
-                        return Err(format!("Could not get gift info: {e}").into());
                     }
-                }
-                Err(e) => {
-                    return Err(format!("Could not get gift info: {e}").into());
-                }
+                    _ => return Err(format!("Could not get gift info: {err}").into()),
+                },
                 Ok(info) => {
                     let name = info["store_listing"]["sku"]["name"].as_str();
                     if name == Some("Nitro Basic") || name.is_none() {
@@ -248,8 +321,8 @@ impl GiftScanner {
             .await;
 
         match res {
-            Err(DApiError::ApiError(e)) => {
-                if e.code == 50050 {
+            Err(err) => match err.api_error() {
+                Some(e) if e.code == JsonErrorCode::GiftAlreadyRedeemed => {
                     let res = self
                         .redeem_dapi
                         .get(&v6Routes::entitlements_giftcode(&code))
@@ -268,13 +341,13 @@ impl GiftScanner {
                         )),
                         Err(e) => Err(format!("Could not get gift info: {e}").into()),
                     }
-                } else if e.code == 10038 {
-                    return Ok((code, GiftRedeemAttempt::Invalid { info: e.to_string().into() }));
-                } else {
-                    return Err(e.to_string().into());
                 }
-            }
-            Err(e) => Err(format!("DApi error while trying to claim gift: {e}").into()),
+                Some(e) if e.code == JsonErrorCode::UnknownGiftCode => {
+                    Ok((code, GiftRedeemAttempt::Invalid { info: e.to_string().into() }))
+                }
+                Some(e) => Err(e.to_string().into()),
+                None => Err(format!("DApi error while trying to claim gift: {err}").into()),
+            },
             Ok(gift) => Ok((
                 code,
                 GiftRedeemAttempt::Success { info: format!("{:#}", gift).into() },
@@ -299,13 +372,10 @@ impl GiftScanner {
                     lm.rest.content.as_deref().unwrap_or(""),
                     self.ignore,
                     guilds,
-                    self.channel_names.len(),
+                    self.cache.stats().channels,
                     lm.rest.author.as_ref().map(|a| a.username.as_str()).unwrap_or("??"),
-                    self.channel_names.get(&lm.rest.channel_id).map(|s| s.as_str()).unwrap_or("??"),
-                    lm.guild_id.as_ref()
-                        .and_then(|g| 
-                            self.guild_names.get(g).map(|s| s.as_str()))
-                        .unwrap_or("??")
+                    &self.channel_name(lm.rest.channel_id),
+                    &self.guild_name(lm.guild_id)
                 )
                 .await;
         } else if msg.starts_with("...ignore") {
@@ -323,14 +393,9 @@ impl GiftScanner {
             .and_modify(|set| {
                 set.remove(&guild.id);
             });
-        self.guild_names.remove(&guild.id);
     }
 
     async fn handle_guild_create(&mut self, guild: &GatewayGuildCreatePayload) {
-        if let GatewayGuildCreatePayload::Available(g) = guild {
-            self.guild_names.insert(g.guild_info.id.clone(), g.guild_info.name.clone());
-        }
-
         let joined_id = match guild {
             GatewayGuildCreatePayload::Available(g) => &g.guild_info.id,
             GatewayGuildCreatePayload::Unavailable(g) => &g.id
@@ -351,14 +416,10 @@ impl GiftScanner {
         if should_leave {
             let res = self
                 .dapi
-                .delete(&v10Routes::users_guilds_leave(joined_id), &())
+                .delete(&v10Routes::users_guilds_leave(*joined_id), &())
                 .await;
-            let name = self
-                .guild_names
-                .get(joined_id)
-                .map(|s| s.as_str())
-                .unwrap_or("???");
-            self.relay.duplicate_guilds(&self.username, name).await;
+            let name = self.guild_name(Some(*joined_id));
+            self.relay.duplicate_guilds(&self.username, &name).await;
             if let Err(e) = res {
                 self.relay
                     .log_error(&self.username, &e, Some("Failed to leave guild"))
@@ -371,7 +432,7 @@ impl GiftScanner {
                 .unwrap()
                 .entry(self.id)
                 .and_modify(|set| {
-                    set.insert(joined_id.to_owned());
+                    set.insert(*joined_id);
                 });
         }
     }
@@ -407,16 +468,8 @@ impl GiftScanner {
                 };
                 
                 lock.entry(self.id).and_modify(|set| {
-                    set.insert(g.guild_info.id.clone());
+                    set.insert(g.guild_info.id);
                 });
-
-                self.guild_names.insert(g.guild_info.id, g.guild_info.name);
-
-                for c in g.channels {
-                    if let Some(name) = c.name {
-                        self.channel_names.insert(c.id, name);
-                    }
-                }
             }
 
             let self_guilds = lock.get(&self.id).unwrap();
@@ -424,7 +477,7 @@ impl GiftScanner {
                 let dups: Vec<_> = self_guilds
                     .intersection(guilds)
                     .filter(|g| **g != self.command_guild)
-                    .map(|g| self.guild_names.get(g).unwrap_or(g))
+                    .map(|g| self.cache.with_guild(*g, |g| g.guild.name.clone()).unwrap_or_else(|| g.to_string().into()))
                     .collect();
                 if !dups.is_empty() {
                     return Err(format!("@{}: Found duplicate guilds: {:?}", name, dups).into());